@@ -12,6 +12,43 @@ extension Article: @unchecked Sendable {}
 extension DiscoveredFeed: @unchecked Sendable {}
 extension OpmlImportResult: @unchecked Sendable {}
 extension ReadingPattern: @unchecked Sendable {}
+extension RefreshReport: @unchecked Sendable {}
+
+/// Progress of an OPML import, as reported by the core
+struct OPMLImportProgress: Sendable {
+    let imported: UInt32
+    let pending: UInt32
+    let skipped: UInt32
+    let failed: UInt32
+    let total: UInt32
+    let currentUrl: String
+
+    /// Entries dealt with so far, whatever their outcome
+    var done: UInt32 { imported + pending + skipped + failed }
+}
+
+/// Forwards import progress from the Rust thread to the main actor
+final class OPMLImportProgressHandler: ImportProgress, @unchecked Sendable {
+    private let onProgress: @MainActor (OPMLImportProgress) -> Void
+
+    init(onProgress: @escaping @MainActor (OPMLImportProgress) -> Void) {
+        self.onProgress = onProgress
+    }
+
+    func onProgress(imported: UInt32, pending: UInt32, skipped: UInt32, failed: UInt32, total: UInt32, currentUrl: String) {
+        let progress = OPMLImportProgress(
+            imported: imported,
+            pending: pending,
+            skipped: skipped,
+            failed: failed,
+            total: total,
+            currentUrl: currentUrl
+        )
+        Task { @MainActor [onProgress] in
+            onProgress(progress)
+        }
+    }
+}
 
 /// Main application state using Swift 5.9 @Observable macro
 @MainActor
@@ -30,6 +67,7 @@ final class AppState {
     private(set) var serendipityArticles: [Article] = []
     private(set) var recentArticles: [Article] = []
     private(set) var readingPatterns: [ReadingPattern] = []
+    private(set) var lastRefreshReports: [RefreshReport] = []
 
     // MARK: - Selection State
 
@@ -105,8 +143,12 @@ final class AppState {
         }
     }
 
-    func addFeed(url: String) async {
-        guard let core else { return }
+    /// Subscribes to a feed or a website. Returns the feeds to choose from
+    /// when the website offers several.
+    @discardableResult
+    func addFeed(url: String) async -> [DiscoveredFeed] {
+        guard let core else { return [] }
+        var candidates: [DiscoveredFeed] = []
 
         isLoading = true
 
@@ -122,24 +164,22 @@ final class AppState {
             feeds.sort { $0.title.localizedCaseInsensitiveCompare($1.title) == .orderedAscending }
             selectedFeedId = feed.id
             await loadArticlesForSelectedFeed()
-        } catch let error as PatinaError {
+        } catch PatinaError.MultipleFeedsFound(let offered) {
+            candidates = offered
+        } catch PatinaError.FeedAlreadyExists {
             // Handle specific error types with friendlier messages
-            let message = error.localizedDescription
-            if message.contains("Feed already exists") {
-                errorMessage = "This feed is already in your library"
-            } else if message.contains("Network error") {
-                errorMessage = "Could not connect to feed. Check the URL and try again."
-            } else if message.contains("Parse error") {
-                errorMessage = "This URL doesn't appear to be a valid RSS/Atom feed."
-            } else {
-                errorMessage = "Failed to add feed: \(message)"
-            }
+            errorMessage = "This feed is already in your library"
+        } catch PatinaError.NetworkError {
+            errorMessage = "Could not connect to feed. Check the URL and try again."
+        } catch PatinaError.ParseError {
+            errorMessage = "This URL doesn't appear to be a valid RSS/Atom feed."
         } catch {
             errorMessage = "Failed to add feed: \(error.localizedDescription)"
         }
 
         PerformanceSignpost.end(.feedRefresh, name: "AddFeed", id: signpostId)
         isLoading = false
+        return candidates
     }
 
     func deleteFeed(_ feedId: Int64) async {
@@ -191,11 +231,12 @@ final class AppState {
 
         do {
             // Run blocking Rust call on background thread to keep UI responsive
-            let refreshedFeeds = try await Task.detached(priority: .userInitiated) {
+            let reports = try await Task.detached(priority: .userInitiated) {
                 try core.refreshAllFeeds()
             }.value
 
-            feeds = refreshedFeeds
+            lastRefreshReports = reports
+            feeds = try core.getAllFeeds()
             if selectedFeedId != nil {
                 await loadArticlesForSelectedFeed()
             }
@@ -268,7 +309,13 @@ final class AppState {
                     fetchedAt: article.fetchedAt,
                     isRead: true,
                     readAt: Int64(Date().timeIntervalSince1970),
-                    feedTitle: article.feedTitle
+                    feedTitle: article.feedTitle,
+                    updatedAt: article.updatedAt,
+                    revision: article.revision,
+                    authors: article.authors,
+                    categories: article.categories,
+                    thumbnailUrl: article.thumbnailUrl,
+                    starred: article.starred
                 )
             }
             // Update feed unread count
@@ -295,7 +342,13 @@ final class AppState {
                     fetchedAt: article.fetchedAt,
                     isRead: false,
                     readAt: nil,
-                    feedTitle: article.feedTitle
+                    feedTitle: article.feedTitle,
+                    updatedAt: article.updatedAt,
+                    revision: article.revision,
+                    authors: article.authors,
+                    categories: article.categories,
+                    thumbnailUrl: article.thumbnailUrl,
+                    starred: article.starred
                 )
             }
             await loadFeeds()
@@ -306,15 +359,19 @@ final class AppState {
 
     // MARK: - OPML Import
 
-    func importOPML(content: String) async -> OpmlImportResult? {
+    func importOPML(
+        content: String,
+        onProgress: @escaping @MainActor (OPMLImportProgress) -> Void = { _ in }
+    ) async -> OpmlImportResult? {
         guard let core else { return nil }
 
         isLoading = true
+        let progress = OPMLImportProgressHandler(onProgress: onProgress)
 
         do {
             // Run blocking Rust call on background thread to keep UI responsive
             let result = try await Task.detached(priority: .userInitiated) {
-                try core.importOpml(opmlContent: content)
+                try core.importOpml(opmlContent: content, progress: progress)
             }.value
 
             await loadFeeds()
@@ -127,8 +127,14 @@ struct AddFeedSheet: View {
 
     private func addFeed(url: String) {
         Task {
-            await appState.addFeed(url: url)
-            dismiss()
+            let candidates = await appState.addFeed(url: url)
+            if candidates.isEmpty {
+                dismiss()
+            } else {
+                // The website offers several feeds, let the user pick one
+                discoveredFeeds = candidates
+                showDiscoveryResults = true
+            }
         }
     }
 
@@ -8,6 +8,7 @@ struct ImportOPMLSheet: View {
 
     @State private var importResult: OpmlImportResult?
     @State private var isImporting = false
+    @State private var progress: OPMLImportProgress?
     @State private var showFilePicker = false
 
     var body: some View {
@@ -97,9 +98,21 @@ struct ImportOPMLSheet: View {
                 VStack(spacing: DesignTokens.Spacing.sm) {
                     ProgressView()
                         .tint(DesignTokens.Colors.accent)
-                    Text("Importing feeds...")
-                        .font(DesignTokens.Typography.bodySmall)
-                        .foregroundStyle(DesignTokens.Colors.textSecondary)
+                    if let progress {
+                        Text("Importing feeds... \(progress.done) of \(progress.total)")
+                            .font(DesignTokens.Typography.bodySmall)
+                            .foregroundStyle(DesignTokens.Colors.textSecondary)
+                        Text(progress.currentUrl)
+                            .font(DesignTokens.Typography.caption)
+                            .foregroundStyle(DesignTokens.Colors.textTertiary)
+                            .lineLimit(1)
+                            .truncationMode(.middle)
+                            .frame(maxWidth: 300)
+                    } else {
+                        Text("Importing feeds...")
+                            .font(DesignTokens.Typography.bodySmall)
+                            .foregroundStyle(DesignTokens.Colors.textSecondary)
+                    }
                 }
                 .padding(DesignTokens.Spacing.lg)
                 .background(DesignTokens.Colors.backgroundElevated)
@@ -121,6 +134,7 @@ struct ImportOPMLSheet: View {
 
     private func importFile(_ url: URL) {
         isImporting = true
+        progress = nil
 
         Task {
             do {
@@ -134,7 +148,9 @@ struct ImportOPMLSheet: View {
                 defer { url.stopAccessingSecurityScopedResource() }
 
                 let content = try String(contentsOf: url, encoding: .utf8)
-                importResult = await appState.importOPML(content: content)
+                importResult = await appState.importOPML(content: content) { update in
+                    progress = update
+                }
             } catch {
                 appState.errorMessage = "Failed to read file: \(error.localizedDescription)"
             }
@@ -147,11 +163,27 @@ struct ImportOPMLSheet: View {
 struct ImportResultView: View {
     let result: OpmlImportResult
 
+    /// Entries that weren't imported as-is
+    private var problems: [OpmlEntryOutcome] {
+        result.entries.filter { $0.status != .imported }
+    }
+
+    private static func describe(_ entry: OpmlEntryOutcome) -> String {
+        switch entry.status {
+        case .imported: "Imported"
+        case .alreadySubscribed: "Already subscribed"
+        case .duplicateInFile: "Listed more than once"
+        case .invalidUrl: "Invalid URL"
+        case .fetchFailed: "Unreachable, will retry (\(entry.error ?? "unknown error"))"
+        case .parseFailed: "Not a feed (\(entry.error ?? "unknown error"))"
+        }
+    }
+
     var body: some View {
         VStack(spacing: DesignTokens.Spacing.md) {
-            Image(systemName: result.failedFeeds == 0 ? "checkmark.circle.fill" : "exclamationmark.triangle.fill")
+            Image(systemName: result.failedFeeds + result.pendingFeeds == 0 ? "checkmark.circle.fill" : "exclamationmark.triangle.fill")
                 .font(.system(size: 48))
-                .foregroundStyle(result.failedFeeds == 0 ? DesignTokens.Colors.success : DesignTokens.Colors.warning)
+                .foregroundStyle(result.failedFeeds + result.pendingFeeds == 0 ? DesignTokens.Colors.success : DesignTokens.Colors.warning)
 
             Text("Import Complete")
                 .font(DesignTokens.Typography.headingMedium)
@@ -178,6 +210,30 @@ struct ImportResultView: View {
                         .foregroundStyle(DesignTokens.Colors.success)
                 }
 
+                if result.skippedFeeds > 0 {
+                    HStack {
+                        Text("Already subscribed:")
+                            .font(DesignTokens.Typography.bodyMedium)
+                            .foregroundStyle(DesignTokens.Colors.textSecondary)
+                        Spacer()
+                        Text("\(result.skippedFeeds)")
+                            .font(DesignTokens.Typography.bodyMedium.weight(.medium))
+                            .foregroundStyle(DesignTokens.Colors.textPrimary)
+                    }
+                }
+
+                if result.pendingFeeds > 0 {
+                    HStack {
+                        Text("Unreachable, will retry:")
+                            .font(DesignTokens.Typography.bodyMedium)
+                            .foregroundStyle(DesignTokens.Colors.textSecondary)
+                        Spacer()
+                        Text("\(result.pendingFeeds)")
+                            .font(DesignTokens.Typography.bodyMedium.weight(.medium))
+                            .foregroundStyle(DesignTokens.Colors.warning)
+                    }
+                }
+
                 if result.failedFeeds > 0 {
                     HStack {
                         Text("Failed:")
@@ -194,12 +250,13 @@ struct ImportResultView: View {
             .background(DesignTokens.Colors.backgroundTertiary)
             .clipShape(RoundedRectangle(cornerRadius: DesignTokens.Radius.md))
 
-            if !result.errors.isEmpty {
+            if !problems.isEmpty {
                 DisclosureGroup {
                     ScrollView {
                         VStack(alignment: .leading, spacing: DesignTokens.Spacing.xs) {
-                            ForEach(result.errors, id: \.self) { error in
-                                Text(error)
+                            ForEach(problems.indices, id: \.self) { index in
+                                let entry = problems[index]
+                                Text("\(entry.title ?? entry.url): \(Self.describe(entry))")
                                     .font(DesignTokens.Typography.caption)
                                     .foregroundStyle(DesignTokens.Colors.textTertiary)
                             }
@@ -208,7 +265,7 @@ struct ImportResultView: View {
                     }
                     .frame(maxHeight: 100)
                 } label: {
-                    Text("Show errors")
+                    Text("Show details")
                         .font(DesignTokens.Typography.bodySmall)
                         .foregroundStyle(DesignTokens.Colors.textSecondary)
                 }
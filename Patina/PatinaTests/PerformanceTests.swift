@@ -180,7 +180,7 @@ final class OPMLPerformanceTests: XCTestCase {
         // This will parse the OPML and fail on each feed fetch (invalid URLs)
         // We're primarily measuring the parsing overhead
         measure {
-            _ = try? core.importOpml(opmlContent: opml)
+            _ = try? core.importOpml(opmlContent: opml, progress: nil)
         }
     }
 
@@ -189,7 +189,7 @@ final class OPMLPerformanceTests: XCTestCase {
         let opml = generateOPML(feedCount: 50)
 
         measure {
-            _ = try? core.importOpml(opmlContent: opml)
+            _ = try? core.importOpml(opmlContent: opml, progress: nil)
         }
     }
 }
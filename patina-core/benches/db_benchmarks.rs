@@ -26,7 +26,7 @@ fn seed_feeds(db: &Database, count: usize) -> Vec<i64> {
             title: format!("Test Feed {}", i),
             url: format!("https://example{}.com/feed.xml", i),
             site_url: Some(format!("https://example{}.com", i)),
-            articles: Vec::new(),
+            ..Default::default()
         };
 
         let inserted = db.insert_feed(&feed).unwrap();
@@ -49,6 +49,7 @@ fn seed_articles(db: &Database, feed_id: i64, count: usize) {
                 i
             )),
             published_at: Some(chrono::Utc::now().timestamp() - (i as i64 * 3600)),
+            ..Default::default()
         };
         let _ = db.insert_article(feed_id, &article);
     }
@@ -183,6 +184,7 @@ fn bench_insert_article(c: &mut Criterion) {
                 url: format!("https://example.com/bench{}", counter),
                 summary: Some("A benchmark article summary with some content.".to_string()),
                 published_at: Some(chrono::Utc::now().timestamp()),
+                ..Default::default()
             };
             let _ = black_box(db.insert_article(feed_id, &article));
         });
@@ -0,0 +1,190 @@
+use crate::PatinaError;
+use crate::feed::parser::fetch_and_parse_feed;
+use crate::storage::db::Database;
+use crate::storage::models::{BackfillReport, Feed};
+use std::collections::HashSet;
+
+/// Import a feed's older entries by walking back through its history.
+///
+/// Feeds that follow RFC 5005 link to older entries with `rel="next"`
+/// (paged feeds) or `rel="prev-archive"` (archived feeds). Feeds without
+/// either are tried with WordPress's `?paged=N`. The walk stops after
+/// `max_pages` documents, at the end of the history, or at the first page
+/// with nothing new, which is also what servers ignoring `paged` produce.
+///
+/// Entries are stored as backfilled, so they don't show up as new.
+pub fn backfill_feed(
+    db: &Database,
+    feed: &Feed,
+    max_pages: u32,
+) -> Result<BackfillReport, PatinaError> {
+    // The live document only supplies the links; refresh stores its entries
+    let current = fetch_and_parse_feed(&feed.url)?;
+
+    let mut seen: HashSet<String> = current.articles.into_iter().map(|a| a.guid).collect();
+    let mut visited = HashSet::from([feed.url.clone()]);
+
+    let paged = current.next_page_url.is_none();
+    let mut next_page_url = current.next_page_url;
+
+    let mut report = BackfillReport {
+        feed_id: feed.id,
+        pages_fetched: 0,
+        articles_added: 0,
+    };
+
+    for page_number in 2..max_pages.saturating_add(2) {
+        let url = if paged {
+            wordpress_page_url(&feed.url, page_number)?
+        } else {
+            match next_page_url.take() {
+                Some(url) => url,
+                None => break,
+            }
+        };
+
+        // Guard against archives that link back to themselves
+        if !visited.insert(url.clone()) {
+            break;
+        }
+
+        // Guessed WordPress pages end at the first error status, even one
+        // served with a feed body; a broken RFC 5005 link only matters if
+        // nothing could be imported at all
+        let page = match fetch_and_parse_feed(&url) {
+            Ok(page) => page,
+            Err(e) if !paged && report.pages_fetched == 0 => return Err(e),
+            Err(_) => break,
+        };
+        report.pages_fetched += 1;
+
+        let mut found_new = false;
+        for article in &page.articles {
+            if !seen.insert(article.guid.clone()) {
+                continue;
+            }
+            found_new = true;
+            if db.insert_backfilled_article(feed.id, article)?.is_some() {
+                report.articles_added += 1;
+            }
+        }
+
+        if !found_new {
+            break;
+        }
+        next_page_url = page.next_page_url;
+    }
+
+    Ok(report)
+}
+
+/// The URL of page `page` of a WordPress feed
+fn wordpress_page_url(feed_url: &str, page: u32) -> Result<String, PatinaError> {
+    let mut url = url::Url::parse(feed_url)?;
+
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "paged")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("paged", &page.to_string());
+
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_db::database_with_feed;
+    use crate::feed::test_server::{response, serve, serve_pages};
+
+    /// An RSS document with the given item numbers, optionally linking onwards
+    fn rss(items: &[u32], next: Option<&str>) -> String {
+        let items: String = items
+            .iter()
+            .map(|i| format!("<item><title>{i}</title><link>https://example.com/{i}</link></item>"))
+            .collect();
+        let next = next
+            .map(|href| format!(r#"<atom:link rel="next" href="{href}"/>"#))
+            .unwrap_or_default();
+
+        format!(
+            r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+            <title>T</title><link>https://example.com</link>{next}{items}</channel></rss>"#
+        )
+    }
+
+    #[test]
+    fn test_backfill_rfc5005() {
+        let base = serve_pages(vec![
+            ("/feed", rss(&[5, 4], Some("/feed/2"))),
+            ("/feed/2", rss(&[3, 2], Some("/feed/3"))),
+            ("/feed/3", rss(&[1], None)),
+        ]);
+
+        let (db, feed_id) = database_with_feed(&format!("{base}/feed"));
+        let feed = db.get_feed(feed_id).unwrap().unwrap();
+
+        let report = backfill_feed(&db, &feed, 10).unwrap();
+        assert_eq!(report.pages_fetched, 2);
+        assert_eq!(report.articles_added, 3);
+
+        // History is kept unread for serendipity but isn't new
+        assert_eq!(db.get_articles_for_feed(feed.id).unwrap().len(), 3);
+        assert!(db.get_all_unread_articles().unwrap().is_empty());
+        assert_eq!(db.get_feed(feed.id).unwrap().unwrap().unread_count, 0);
+        assert_eq!(
+            db.get_unread_articles_with_topics(&[], 10).unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_backfill_wordpress() {
+        let base = serve_pages(vec![
+            ("/feed/", rss(&[5, 4], None)),
+            ("/feed/?paged=2", rss(&[3, 2], None)),
+        ]);
+
+        let (db, feed_id) = database_with_feed(&format!("{base}/feed/"));
+        let feed = db.get_feed(feed_id).unwrap().unwrap();
+
+        // Page 3 doesn't exist
+        let report = backfill_feed(&db, &feed, 5).unwrap();
+        assert_eq!(report.pages_fetched, 1);
+        assert_eq!(report.articles_added, 2);
+    }
+
+    #[test]
+    fn test_backfill_wordpress_error_status() {
+        // Some sites answer pages past the end with their feed and a 404
+        let base = serve(|request| match request.path.as_str() {
+            "/feed/" => response("200 OK", &[], rss(&[5, 4], None)),
+            "/feed/?paged=2" => response("200 OK", &[], rss(&[3, 2], None)),
+            _ => response("404 Not Found", &[], rss(&[1], None)),
+        });
+
+        let (db, feed_id) = database_with_feed(&format!("{base}/feed/"));
+        let feed = db.get_feed(feed_id).unwrap().unwrap();
+
+        let report = backfill_feed(&db, &feed, 5).unwrap();
+        assert_eq!(report.pages_fetched, 1);
+        assert_eq!(report.articles_added, 2);
+    }
+
+    #[test]
+    fn test_wordpress_page_url() {
+        assert_eq!(
+            wordpress_page_url("https://example.com/feed/", 2).unwrap(),
+            "https://example.com/feed/?paged=2"
+        );
+        assert_eq!(
+            wordpress_page_url("https://example.com/?feed=rss2&paged=2", 3).unwrap(),
+            "https://example.com/?feed=rss2&paged=3"
+        );
+    }
+}
@@ -0,0 +1,374 @@
+pub mod platforms;
+
+use crate::PatinaError;
+use crate::feed::http::{create_async_client, create_client};
+use crate::feed::parser::parse_feed_content;
+use crate::storage::models::{DiscoveredFeed, DiscoverySource, ParsedFeed};
+use scraper::{Html, Selector};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Candidates fetched at the same time while probing
+const MAX_CONCURRENT_PROBES: usize = 8;
+
+/// Discover RSS/Atom/JSON feeds from a website URL.
+/// Only candidates that really are feeds are returned, best first.
+pub fn discover_feeds(website_url: &str) -> Result<Vec<DiscoveredFeed>, PatinaError> {
+    // Well-known platforms map straight to their feeds, without the page
+    let page_url = url::Url::parse(website_url)?;
+    let feeds = discover_platform_feeds(&page_url)?;
+    if !feeds.is_empty() {
+        return Ok(feeds.into_iter().map(|probed| probed.discovered).collect());
+    }
+
+    let client = create_client()?;
+    let response = client.get(website_url).send()?;
+
+    // Resolve links against the final URL in case the site redirected
+    let base_url = response.url().clone();
+    let html = response.text()?;
+
+    let feeds = discover_feeds_in_page(&html, &base_url)?;
+    Ok(feeds.into_iter().map(|probed| probed.discovered).collect())
+}
+
+/// Feeds a well-known platform publishes for a page, from its URL alone,
+/// keeping what each parsed to
+pub fn discover_platform_feeds(page_url: &url::Url) -> Result<Vec<ProbedFeed>, PatinaError> {
+    let candidates: Vec<FeedCandidate> =
+        platforms::platform_feed_urls(page_url, &platforms::default_rules())
+            .into_iter()
+            .map(|url| FeedCandidate {
+                url,
+                source: DiscoverySource::Platform,
+            })
+            .collect();
+
+    probe_candidates(candidates)
+}
+
+/// Discover feeds from a web page that has already been downloaded, keeping
+/// what each parsed to
+pub fn discover_feeds_in_page(
+    html: &str,
+    base_url: &url::Url,
+) -> Result<Vec<ProbedFeed>, PatinaError> {
+    probe_candidates(parse_feed_links(html, base_url)?)
+}
+
+/// Find a website's icon: the largest `<link rel="icon">` or
+/// `apple-touch-icon` its home page declares, else `/favicon.ico` if the
+/// site serves one
+pub fn discover_favicon(website_url: &str) -> Result<Option<String>, PatinaError> {
+    let client = create_client()?;
+    let response = client.get(website_url).send()?;
+    let base_url = response.url().clone();
+    let html = response.text()?;
+
+    if let Some(icon) = parse_icon_links(&html, &base_url) {
+        return Ok(Some(icon));
+    }
+
+    let fallback = base_url.join("/favicon.ico")?;
+    let found = client
+        .head(fallback.as_str())
+        .send()
+        .is_ok_and(|response| response.status().is_success());
+
+    Ok(found.then(|| fallback.to_string()))
+}
+
+/// Pick the largest icon declared in a page's `<link>` elements
+fn parse_icon_links(html: &str, base_url: &url::Url) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href]").unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|link| {
+            let value = link.value();
+            let rel = value.attr("rel")?.to_ascii_lowercase();
+            let touch_icon = rel.split_whitespace().any(|r| r == "apple-touch-icon");
+            if !touch_icon && !rel.split_whitespace().any(|r| r == "icon") {
+                return None;
+            }
+
+            // Undeclared sizes are usually a 16px favicon, or 180px for touch icons
+            let size = value
+                .attr("sizes")
+                .and_then(|sizes| {
+                    sizes
+                        .split_whitespace()
+                        .filter_map(|size| {
+                            if size.eq_ignore_ascii_case("any") {
+                                return Some(u32::MAX);
+                            }
+                            size.split(['x', 'X']).next()?.parse::<u32>().ok()
+                        })
+                        .max()
+                })
+                .unwrap_or(if touch_icon { 180 } else { 16 });
+
+            let url = base_url.join(value.attr("href")?).ok()?;
+            Some((size, url.to_string()))
+        })
+        .max_by_key(|(size, _)| *size)
+        .map(|(_, url)| url)
+}
+
+/// A URL that might be a feed, before it has been probed
+#[derive(Debug, Clone)]
+struct FeedCandidate {
+    url: String,
+    source: DiscoverySource,
+}
+
+/// A candidate that turned out to be a feed, with the feed it parsed to
+#[derive(Debug)]
+pub struct ProbedFeed {
+    pub discovered: DiscoveredFeed,
+    pub feed: ParsedFeed,
+}
+
+/// Parse HTML to find feed links, in the order they should be ranked:
+/// declared `<link rel="alternate">` feeds, then feed-like `<a>` links, then
+/// common feed paths on the site
+fn parse_feed_links(html: &str, base_url: &url::Url) -> Result<Vec<FeedCandidate>, PatinaError> {
+    let document = Html::parse_document(html);
+
+    let declared = Selector::parse(
+        r#"link[rel="alternate"][type="application/rss+xml"],
+           link[rel="alternate"][type="application/atom+xml"],
+           link[rel="alternate"][type="text/xml"],
+           link[rel="alternate"][type="application/feed+json"]"#,
+    )
+    .map_err(|e| PatinaError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let anchors = Selector::parse(r#"a[href*="rss"], a[href*="feed"], a[href*="atom"]"#)
+        .map_err(|e| PatinaError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let mut candidates = Vec::new();
+    let mut seen_urls = std::collections::HashSet::new();
+
+    let mut push = |url: url::Url, source| {
+        // Only web URLs can be probed; skips mailto:, javascript: and the like
+        if matches!(url.scheme(), "http" | "https") && seen_urls.insert(url.to_string()) {
+            candidates.push(FeedCandidate {
+                url: url.to_string(),
+                source,
+            });
+        }
+    };
+
+    for (selector, source) in [
+        (&declared, DiscoverySource::LinkRel),
+        (&anchors, DiscoverySource::Anchor),
+    ] {
+        for element in document.select(selector) {
+            if let Some(url) = element
+                .value()
+                .attr("href")
+                .and_then(|href| base_url.join(href).ok())
+            {
+                push(url, source);
+            }
+        }
+    }
+
+    // Also check common feed URL patterns
+    let common_patterns = [
+        "/feed",
+        "/feed/",
+        "/rss",
+        "/rss.xml",
+        "/atom.xml",
+        "/feed.xml",
+        "/index.xml",
+    ];
+
+    for pattern in common_patterns {
+        if let Ok(url) = base_url.join(pattern) {
+            push(url, DiscoverySource::CommonPath);
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// Fetch every candidate concurrently and keep those that parse as feeds,
+/// best ranked first. Candidates redirecting to the same feed are merged.
+fn probe_candidates(candidates: Vec<FeedCandidate>) -> Result<Vec<ProbedFeed>, PatinaError> {
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client = create_async_client()?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let mut probed: Vec<(usize, ProbedFeed)> = runtime.block_on(async {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_PROBES));
+        let mut tasks = JoinSet::new();
+
+        for (rank, candidate) in candidates.into_iter().enumerate() {
+            let client = client.clone();
+            let permits = Arc::clone(&permits);
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                probe(&client, candidate).await.map(|feed| (rank, feed))
+            });
+        }
+
+        let mut probed = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            if let Ok(Some(found)) = result {
+                probed.push(found);
+            }
+        }
+        probed
+    });
+
+    // Sources are already in rank order; within one, keep the page's order
+    probed.sort_by_key(|(rank, probed)| (probed.discovered.source, *rank));
+
+    let mut seen_urls = std::collections::HashSet::new();
+    Ok(probed
+        .into_iter()
+        .map(|(_, probed)| probed)
+        .filter(|probed| seen_urls.insert(probed.discovered.url.clone()))
+        .collect())
+}
+
+/// Fetch a candidate and describe it if it is a working feed
+async fn probe(client: &reqwest::Client, candidate: FeedCandidate) -> Option<ProbedFeed> {
+    let response = client.get(&candidate.url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    // Report where the feed really lives if the candidate redirected
+    let url = response.url().to_string();
+    let bytes = response.bytes().await.ok()?;
+    let feed = parse_feed_content(&bytes, &url).ok()?;
+
+    let discovered = DiscoveredFeed {
+        url,
+        title: Some(feed.title.clone()),
+        format: feed.format,
+        entry_count: feed.articles.len() as i32,
+        source: candidate.source,
+    };
+    Some(ProbedFeed { discovered, feed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_server::serve_pages;
+    use crate::storage::models::FeedFormat;
+
+    #[test]
+    fn test_parse_feed_links() {
+        let html = r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <link rel="alternate" type="application/rss+xml" title="RSS Feed" href="/feed.xml">
+            <link rel="alternate" type="application/atom+xml" title="Atom Feed" href="/atom.xml">
+            <link rel="alternate" type="application/feed+json" title="JSON Feed" href="/feed.json">
+        </head>
+        <body></body>
+        </html>
+        "#;
+
+        let base_url = url::Url::parse("https://example.com").unwrap();
+        let feeds = parse_feed_links(html, &base_url).unwrap();
+
+        assert!(!feeds.is_empty());
+        assert!(feeds.iter().any(|f| f.url.contains("feed.xml")));
+        assert!(feeds.iter().any(|f| f.url.contains("atom.xml")));
+        assert!(feeds.iter().any(|f| f.url.contains("feed.json")));
+    }
+
+    #[test]
+    fn test_parse_icon_links() {
+        let base_url = url::Url::parse("https://example.com/blog/").unwrap();
+
+        let html = r#"<html><head>
+            <link rel="shortcut icon" href="/favicon.ico">
+            <link rel="icon" type="image/png" sizes="32x32" href="/icon-32.png">
+            <link rel="apple-touch-icon" href="touch.png">
+            <link rel="stylesheet" href="/style.css">
+        </head></html>"#;
+        assert_eq!(
+            parse_icon_links(html, &base_url).as_deref(),
+            Some("https://example.com/blog/touch.png")
+        );
+
+        let html = r#"<link rel="icon" href="/favicon.ico"><link rel="icon" sizes="any" href="/icon.svg">"#;
+        assert_eq!(
+            parse_icon_links(html, &base_url).as_deref(),
+            Some("https://example.com/icon.svg")
+        );
+
+        assert!(parse_icon_links("<html></html>", &base_url).is_none());
+    }
+
+    #[test]
+    fn test_discover_feeds_probes_candidates() {
+        let page = r#"<html><head>
+            <link rel="alternate" type="application/atom+xml" title="Declared" href="/atom.xml">
+            <link rel="alternate" type="application/rss+xml" href="/dead.xml">
+        </head><body>
+            <a href="/rss">RSS</a>
+            <a href="/feed-of-thoughts">Not a feed</a>
+        </body></html>"#;
+        let rss = r#"<rss version="2.0"><channel><title>Posts</title><link>https://example.com</link>
+            <item><title>1</title><link>https://example.com/1</link></item>
+            <item><title>2</title><link>https://example.com/2</link></item></channel></rss>"#;
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Real Title</title><id>urn:t</id></feed>"#;
+
+        let base = serve_pages(vec![
+            ("/", page.to_string()),
+            ("/atom.xml", atom.to_string()),
+            ("/rss", rss.to_string()),
+            ("/index.xml", rss.to_string()),
+            (
+                "/feed-of-thoughts",
+                "<html><body>Hello</body></html>".to_string(),
+            ),
+        ]);
+
+        let feeds = discover_feeds(&format!("{base}/")).unwrap();
+        let urls: Vec<_> = feeds
+            .iter()
+            .map(|f| f.url.trim_start_matches(&base))
+            .collect();
+        assert_eq!(urls, vec!["/atom.xml", "/rss", "/index.xml"]);
+
+        assert_eq!(feeds[0].title.as_deref(), Some("Real Title"));
+        assert_eq!(feeds[0].format, Some(FeedFormat::Atom));
+        assert_eq!(feeds[0].source, DiscoverySource::LinkRel);
+        assert_eq!(feeds[1].entry_count, 2);
+        assert_eq!(feeds[1].source, DiscoverySource::Anchor);
+        assert_eq!(feeds[2].source, DiscoverySource::CommonPath);
+    }
+
+    #[test]
+    fn test_discover_feeds_in_page_keeps_parsed_feed() {
+        let rss = r#"<rss version="2.0"><channel><title>Posts</title><link>https://example.com</link>
+            <item><title>1</title><link>https://example.com/1</link></item></channel></rss>"#;
+        let base = serve_pages(vec![("/rss", rss.to_string())]);
+
+        let page = r#"<link rel="alternate" type="application/rss+xml" href="/rss">"#;
+        let base_url = url::Url::parse(&format!("{base}/")).unwrap();
+        let probed = discover_feeds_in_page(page, &base_url).unwrap();
+
+        assert_eq!(probed.len(), 1);
+        assert_eq!(probed[0].discovered.url, format!("{base}/rss"));
+        assert_eq!(probed[0].feed.url, probed[0].discovered.url);
+        assert_eq!(probed[0].feed.articles.len(), 1);
+    }
+}
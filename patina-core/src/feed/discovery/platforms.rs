@@ -0,0 +1,243 @@
+use url::Url;
+
+/// Maps pages on a well-known platform to the feeds the platform publishes
+/// for them, from the URL alone
+pub trait PlatformRule: Send + Sync {
+    /// Feed URLs for `page`, best first, or none if the page isn't one this
+    /// rule knows
+    fn feed_urls(&self, page: &Url) -> Vec<String>;
+}
+
+/// The rules `discover_feeds` applies before looking at the page itself
+pub fn default_rules() -> Vec<Box<dyn PlatformRule>> {
+    vec![
+        Box::new(YouTube),
+        Box::new(Reddit),
+        Box::new(GitHub),
+        Box::new(Substack),
+        Box::new(Mastodon),
+    ]
+}
+
+/// Feed URLs from the first rule that recognises `page`
+pub fn platform_feed_urls(page: &Url, rules: &[Box<dyn PlatformRule>]) -> Vec<String> {
+    rules
+        .iter()
+        .map(|rule| rule.feed_urls(page))
+        .find(|urls| !urls.is_empty())
+        .unwrap_or_default()
+}
+
+/// Whether `url` is on `domain` or one of its subdomains
+fn on_domain(url: &Url, domain: &str) -> bool {
+    url.host_str().is_some_and(|host| {
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+/// Non-empty path segments of a URL
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// YouTube channels and playlists. `@handle` pages need the channel id from
+/// the page, which declares its feed, so they are left to HTML discovery.
+pub struct YouTube;
+
+impl PlatformRule for YouTube {
+    fn feed_urls(&self, page: &Url) -> Vec<String> {
+        if !on_domain(page, "youtube.com") {
+            return Vec::new();
+        }
+
+        let feed = |param: &str, value: &str| {
+            let mut url = Url::parse("https://www.youtube.com/feeds/videos.xml").unwrap();
+            url.query_pairs_mut().append_pair(param, value);
+            vec![url.to_string()]
+        };
+
+        if let Some((_, list)) = page.query_pairs().find(|(key, _)| key == "list") {
+            return feed("playlist_id", &list);
+        }
+
+        match segments(page).as_slice() {
+            ["channel", id, ..] => feed("channel_id", id),
+            ["user", name, ..] => feed("user", name),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Subreddits, users and comment threads, which all have an `.rss` twin
+pub struct Reddit;
+
+impl PlatformRule for Reddit {
+    fn feed_urls(&self, page: &Url) -> Vec<String> {
+        if !on_domain(page, "reddit.com") {
+            return Vec::new();
+        }
+
+        let path = match segments(page).as_slice() {
+            ["r", name, rest @ ..] => [&["r", *name], rest].concat(),
+            ["u" | "user", name, ..] => vec!["user", *name],
+            _ => return Vec::new(),
+        };
+
+        vec![format!("https://www.reddit.com/{}/.rss", path.join("/"))]
+    }
+}
+
+/// Repository releases, tags and commits, and user activity
+pub struct GitHub;
+
+impl PlatformRule for GitHub {
+    fn feed_urls(&self, page: &Url) -> Vec<String> {
+        if page.host_str() != Some("github.com") && page.host_str() != Some("www.github.com") {
+            return Vec::new();
+        }
+
+        match segments(page).as_slice() {
+            [owner, repo, ..] => {
+                let repo = repo.trim_end_matches(".git");
+                ["releases", "tags", "commits"]
+                    .iter()
+                    .map(|feed| format!("https://github.com/{owner}/{repo}/{feed}.atom"))
+                    .collect()
+            }
+            [user] => vec![format!("https://github.com/{user}.atom")],
+            [] => Vec::new(),
+        }
+    }
+}
+
+/// Substack newsletters on their substack.com subdomain
+pub struct Substack;
+
+impl PlatformRule for Substack {
+    fn feed_urls(&self, page: &Url) -> Vec<String> {
+        match page.host_str() {
+            Some(host) if on_domain(page, "substack.com") && host.split('.').count() == 3 => {
+                if host.starts_with("www.") {
+                    return Vec::new();
+                }
+                vec![format!("https://{host}/feed")]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Sites with `/@user` pages that aren't fediverse profiles
+const NON_FEDIVERSE_HANDLE_HOSTS: &[&str] = &["youtube.com", "medium.com", "tiktok.com"];
+
+/// Mastodon (and compatible) profiles, `https://instance/@user`. Any server
+/// could be an instance, so the feed is verified when it is probed.
+pub struct Mastodon;
+
+impl PlatformRule for Mastodon {
+    fn feed_urls(&self, page: &Url) -> Vec<String> {
+        let segments = segments(page);
+        let (Some(host), [user]) = (page.host_str(), segments.as_slice()) else {
+            return Vec::new();
+        };
+        if NON_FEDIVERSE_HANDLE_HOSTS
+            .iter()
+            .any(|domain| on_domain(page, domain))
+        {
+            return Vec::new();
+        }
+
+        let is_handle = user.strip_prefix('@').is_some_and(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        });
+
+        if is_handle {
+            vec![format!("{}://{host}/{user}.rss", page.scheme())]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feeds(page: &str) -> Vec<String> {
+        platform_feed_urls(&Url::parse(page).unwrap(), &default_rules())
+    }
+
+    #[test]
+    fn test_youtube() {
+        assert_eq!(
+            feeds("https://www.youtube.com/channel/UC1234abcd/videos"),
+            vec!["https://www.youtube.com/feeds/videos.xml?channel_id=UC1234abcd"]
+        );
+        assert_eq!(
+            feeds("https://m.youtube.com/playlist?list=PL42"),
+            vec!["https://www.youtube.com/feeds/videos.xml?playlist_id=PL42"]
+        );
+        assert_eq!(
+            feeds("https://youtube.com/user/someone"),
+            vec!["https://www.youtube.com/feeds/videos.xml?user=someone"]
+        );
+        // Handles can't be resolved without the page
+        assert!(feeds("https://www.youtube.com/@someone").is_empty());
+    }
+
+    #[test]
+    fn test_reddit() {
+        assert_eq!(
+            feeds("https://www.reddit.com/r/rust/"),
+            vec!["https://www.reddit.com/r/rust/.rss"]
+        );
+        assert_eq!(
+            feeds("https://old.reddit.com/r/rust/comments/abc/title/"),
+            vec!["https://www.reddit.com/r/rust/comments/abc/title/.rss"]
+        );
+        assert_eq!(
+            feeds("https://reddit.com/u/spez"),
+            vec!["https://www.reddit.com/user/spez/.rss"]
+        );
+        assert!(feeds("https://www.reddit.com/").is_empty());
+    }
+
+    #[test]
+    fn test_github() {
+        assert_eq!(
+            feeds("https://github.com/rust-lang/rust/tree/master"),
+            vec![
+                "https://github.com/rust-lang/rust/releases.atom",
+                "https://github.com/rust-lang/rust/tags.atom",
+                "https://github.com/rust-lang/rust/commits.atom",
+            ]
+        );
+        assert_eq!(
+            feeds("https://github.com/octocat"),
+            vec!["https://github.com/octocat.atom"]
+        );
+    }
+
+    #[test]
+    fn test_substack_and_mastodon() {
+        assert_eq!(
+            feeds("https://someone.substack.com/p/a-post"),
+            vec!["https://someone.substack.com/feed"]
+        );
+        assert!(feeds("https://substack.com/browse").is_empty());
+
+        assert_eq!(
+            feeds("https://mastodon.social/@Gargron"),
+            vec!["https://mastodon.social/@Gargron.rss"]
+        );
+        assert!(feeds("https://example.com/about").is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::PatinaError;
+use crate::feed::http::create_download_client;
+use crate::storage::models::Enclosure;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, HeaderMap, RANGE};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes downloaded between progress reports
+const PROGRESS_INTERVAL_BYTES: u64 = 256 * 1024;
+
+/// Longest file name kept from an enclosure URL
+const MAX_FILE_NAME_CHARS: usize = 100;
+
+/// Receives progress while an enclosure downloads
+#[uniffi::export(with_foreign)]
+pub trait DownloadProgress: Send + Sync {
+    /// `total_bytes` is `None` when the server doesn't say how large the file is
+    fn on_progress(&self, enclosure_id: i64, downloaded_bytes: u64, total_bytes: Option<u64>);
+}
+
+/// Download an enclosure into `directory`, returning the saved file's path.
+///
+/// Data goes to a `.part` file until the download completes. When one is
+/// left over from an interrupted download only the rest is requested, with a
+/// `Range` header; servers that ignore it send the whole file again.
+pub fn download_enclosure(
+    enclosure: &Enclosure,
+    directory: &Path,
+    progress: Option<&dyn DownloadProgress>,
+) -> Result<PathBuf, PatinaError> {
+    fs::create_dir_all(directory)?;
+    let name = file_name(enclosure);
+    let path = directory.join(&name);
+    let partial = directory.join(format!("{name}.part"));
+
+    let offset = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+    let client = create_download_client()?;
+    let mut request = client.get(&enclosure.url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
+    let mut response = request.send()?;
+
+    let (offset, total) = match response.status() {
+        StatusCode::PARTIAL_CONTENT => (offset, content_range_total(response.headers())),
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+            // The partial file already holds everything, unless the file
+            // changed on the server since; then start over
+            if content_range_total(response.headers()) == Some(offset) {
+                fs::rename(&partial, &path)?;
+                return Ok(path);
+            }
+            fs::remove_file(&partial)?;
+            return download_enclosure(enclosure, directory, progress);
+        }
+        _ => {
+            response = response.error_for_status()?;
+            (0, response.content_length())
+        }
+    };
+
+    let mut options = OpenOptions::new();
+    if offset > 0 {
+        options.append(true);
+    } else {
+        options.write(true).create(true).truncate(true);
+    }
+    let mut file = options.open(&partial)?;
+
+    let report = |downloaded| {
+        if let Some(progress) = progress {
+            progress.on_progress(enclosure.id, downloaded, total);
+        }
+    };
+
+    let mut downloaded = offset;
+    let mut reported = offset;
+    let mut buf = vec![0; 64 * 1024];
+    report(downloaded);
+
+    loop {
+        let read = response.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buf[..read])?;
+        downloaded += read as u64;
+
+        if downloaded - reported >= PROGRESS_INTERVAL_BYTES {
+            report(downloaded);
+            reported = downloaded;
+        }
+    }
+    file.flush()?;
+
+    // The partial file is kept so the next attempt can resume
+    if total.is_some_and(|total| downloaded < total) {
+        return Err(PatinaError::NetworkError(format!(
+            "Download ended after {downloaded} bytes"
+        )));
+    }
+
+    if reported != downloaded {
+        report(downloaded);
+    }
+
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+/// Size of the whole file from a `Content-Range: bytes 0-99/1234` header
+fn content_range_total(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Name of the downloaded file: the enclosure id, followed by the file name
+/// from its URL with anything unsafe in a path replaced
+fn file_name(enclosure: &Enclosure) -> String {
+    let remote = url::Url::parse(&enclosure.url)
+        .ok()
+        .and_then(|url| url.path_segments()?.next_back().map(str::to_string))
+        .map(|name| {
+            name.chars()
+                .take(MAX_FILE_NAME_CHARS)
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|name| !name.trim_matches(['.', '_']).is_empty());
+
+    match remote {
+        Some(remote) => format!("{}-{}", enclosure.id, remote),
+        None => enclosure.id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_server::{response, serve};
+    use std::sync::Mutex;
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    /// Serve `BODY`, honouring `Range` requests unless `ranges` is false.
+    /// Returns the file's URL.
+    fn serve_body(ranges: bool) -> String {
+        let base = serve(move |request| {
+            let start = request
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok())
+                .filter(|_| ranges);
+
+            match start {
+                Some(start) => {
+                    let range = format!("bytes {}-{}/{}", start, BODY.len() - 1, BODY.len());
+                    response(
+                        "206 Partial Content",
+                        &[("Content-Range", &range)],
+                        &BODY[start..],
+                    )
+                }
+                None => response("200 OK", &[], BODY),
+            }
+        });
+
+        format!("{}/media/episode%201.mp3", base)
+    }
+
+    fn enclosure(url: String) -> Enclosure {
+        Enclosure {
+            id: 7,
+            article_id: 1,
+            url,
+            mime_type: Some("audio/mpeg".to_string()),
+            length: None,
+            duration_secs: None,
+            local_path: None,
+            downloaded_at: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(u64, Option<u64>)>>);
+
+    impl DownloadProgress for Recorder {
+        fn on_progress(&self, _enclosure_id: i64, downloaded_bytes: u64, total_bytes: Option<u64>) {
+            self.0.lock().unwrap().push((downloaded_bytes, total_bytes));
+        }
+    }
+
+    #[test]
+    fn test_download_enclosure() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::default();
+
+        let path =
+            download_enclosure(&enclosure(serve_body(true)), dir.path(), Some(&recorder)).unwrap();
+
+        assert_eq!(path.file_name().unwrap(), "7-episode_201.mp3");
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert!(!dir.path().join("7-episode_201.mp3.part").exists());
+
+        let total = Some(BODY.len() as u64);
+        let reports = recorder.0.lock().unwrap();
+        assert_eq!(reports.first(), Some(&(0, total)));
+        assert_eq!(reports.last(), Some(&(BODY.len() as u64, total)));
+    }
+
+    #[test]
+    fn test_download_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("7-episode_201.mp3.part");
+
+        // Only the missing bytes are requested and appended
+        fs::write(&partial, &BODY[..10]).unwrap();
+        let recorder = Recorder::default();
+        let path =
+            download_enclosure(&enclosure(serve_body(true)), dir.path(), Some(&recorder)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert_eq!(recorder.0.lock().unwrap()[0].0, 10);
+
+        // A server that ignores the range sends everything again
+        fs::remove_file(&path).unwrap();
+        fs::write(&partial, b"stale").unwrap();
+        let path = download_enclosure(&enclosure(serve_body(false)), dir.path(), None).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+    }
+
+    #[test]
+    fn test_file_name() {
+        let name = |url: &str| file_name(&enclosure(url.to_string()));
+        assert_eq!(name("https://cdn.example.com/ep/1.mp3?x=1"), "7-1.mp3");
+        assert_eq!(name("https://cdn.example.com/../"), "7");
+        assert_eq!(name("not a url"), "7");
+    }
+}
@@ -0,0 +1,376 @@
+use crate::storage::models::{PodcastEpisode, PollHints};
+use chrono::Weekday;
+use quick_xml::encoding::Decoder;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::{NsReader, Reader, XmlVersion};
+use std::collections::HashMap;
+use url::Url;
+
+/// Namespace of Apple's podcast tags
+const ITUNES_NS: &[u8] = b"http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+/// Seconds in a `sy:updatePeriod` unit
+fn update_period_secs(period: &str) -> Option<i64> {
+    match period.trim().to_ascii_lowercase().as_str() {
+        "hourly" => Some(60 * 60),
+        "daily" => Some(24 * 60 * 60),
+        "weekly" => Some(7 * 24 * 60 * 60),
+        "monthly" => Some(30 * 24 * 60 * 60),
+        "yearly" => Some(365 * 24 * 60 * 60),
+        _ => None,
+    }
+}
+
+/// Read the channel-level polling hints feed-rs doesn't model:
+/// RSS `<skipHours>`/`<skipDays>` and the syndication module's
+/// `sy:updatePeriod`/`sy:updateFrequency`.
+pub fn parse_poll_hints(content: &[u8]) -> PollHints {
+    let mut reader = Reader::from_reader(content);
+    reader.config_mut().trim_text(true);
+
+    let mut hints = PollHints::default();
+    let mut period = None;
+    let mut frequency = None;
+
+    // Local names of the currently open elements
+    let mut path: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).into_owned());
+            }
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(text)) => {
+                // Entries can't carry channel hints
+                if path.iter().any(|p| p == "item" || p == "entry") {
+                    buf.clear();
+                    continue;
+                }
+
+                let text = text.decode().unwrap_or_default();
+                let parent = path.iter().rev().nth(1).map(String::as_str);
+
+                match (parent, path.last().map(String::as_str)) {
+                    (Some("skipHours"), Some("hour")) => {
+                        // Some publishers write 24 for midnight
+                        if let Ok(hour) = text.trim().parse::<u32>() {
+                            hints.skip_hours.push(hour % 24);
+                        }
+                    }
+                    (Some("skipDays"), Some("day")) => {
+                        if let Ok(day) = text.trim().parse::<Weekday>() {
+                            hints.skip_days.push(day);
+                        }
+                    }
+                    (_, Some("updatePeriod")) => period = update_period_secs(&text),
+                    (_, Some("updateFrequency")) => frequency = text.trim().parse::<i64>().ok(),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    // updateFrequency is the number of updates per period, defaulting to 1
+    hints.update_period_secs = period.map(|secs| secs / frequency.unwrap_or(1).max(1));
+
+    hints
+}
+
+/// An RSS `<item>` as far as podcast metadata is concerned
+#[derive(Default)]
+struct PodcastItem {
+    episode: PodcastEpisode,
+    guid: String,
+    link: String,
+}
+
+/// Read the iTunes podcast metadata of the RSS `<item>`s, keyed by each item's
+/// guid, else its link (see [`podcast_key`]).
+///
+/// feed-rs folds some of these tags into its media objects but drops episode
+/// and season numbers and item-level `itunes:explicit`. Items without their
+/// own `itunes:explicit` or `itunes:image` inherit the channel's. Items with
+/// nothing to report, or nothing to key them by, are left out.
+pub fn parse_podcast_episodes(content: &[u8]) -> HashMap<String, PodcastEpisode> {
+    let mut reader = NsReader::from_reader(content);
+    reader.config_mut().trim_text(true);
+
+    let mut channel = PodcastEpisode::default();
+    let mut items: Vec<PodcastItem> = Vec::new();
+    let mut in_item = false;
+
+    // Local name of the iTunes element whose text is being read
+    let mut field: Option<Vec<u8>> = None;
+    // The item's own <guid> or <link> whose text is being read
+    let mut key_field: Option<Vec<u8>> = None;
+    let mut buf = Vec::new();
+
+    while let Ok((ns, event)) = reader.read_resolved_event_into(&mut buf) {
+        let is_itunes = matches!(ns, ResolveResult::Bound(Namespace(ns)) if ns == ITUNES_NS);
+        // RSS 0.9x and 2.0 elements aren't in any namespace
+        let is_rss = matches!(ns, ResolveResult::Unbound);
+
+        match event {
+            Event::Start(e) if is_rss && e.local_name().as_ref() == b"item" => {
+                items.push(PodcastItem::default());
+                in_item = true;
+            }
+            Event::Start(e)
+                if is_rss && in_item && matches!(e.local_name().as_ref(), b"guid" | b"link") =>
+            {
+                key_field = Some(e.local_name().as_ref().to_vec());
+            }
+            Event::Start(_) | Event::Empty(_) if !is_itunes => {}
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"image" => {
+                let target = if in_item { items.last_mut() } else { None };
+                let target = target.map_or(&mut channel, |item| &mut item.episode);
+                target.image_url = image_href(&e, reader.decoder());
+            }
+            Event::Start(e) => field = Some(e.local_name().as_ref().to_vec()),
+            Event::Text(text) => {
+                let text = text.decode().unwrap_or_default();
+                if let (Some(key_field), Some(item)) = (&key_field, items.last_mut()) {
+                    push_key_text(item, key_field, &text);
+                } else if let Some(field) = &field {
+                    let target = if in_item { items.last_mut() } else { None };
+                    let target = target.map_or(&mut channel, |item| &mut item.episode);
+                    set_episode_field(target, field, text.trim());
+                }
+            }
+            Event::CData(text) => {
+                if let (Some(key_field), Some(item)) = (&key_field, items.last_mut()) {
+                    push_key_text(item, key_field, &text.decode().unwrap_or_default());
+                }
+            }
+            // Text is split around entity references like `&amp;`
+            Event::GeneralRef(reference) => {
+                if let (Some(key_field), Some(item)) = (&key_field, items.last_mut()) {
+                    let name = reference.decode().unwrap_or_default();
+                    let resolved = match reference.resolve_char_ref() {
+                        Ok(Some(ch)) => Some(ch.to_string()),
+                        _ => resolve_predefined_entity(&name).map(str::to_string),
+                    };
+                    push_key_text(item, key_field, &resolved.unwrap_or_default());
+                }
+            }
+            Event::End(e) => {
+                field = None;
+                key_field = None;
+                if is_rss && e.local_name().as_ref() == b"item" {
+                    in_item = false;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let mut episodes = HashMap::new();
+    for item in items {
+        let mut episode = item.episode;
+        episode.explicit = episode.explicit.or(channel.explicit);
+        episode.image_url = episode.image_url.or_else(|| channel.image_url.clone());
+
+        if episode == PodcastEpisode::default() {
+            continue;
+        }
+
+        let link = Some(item.link.trim()).filter(|link| !link.is_empty());
+        if let Some(key) = podcast_key(&item.guid, link) {
+            // Articles are stored by guid, so a repeated one is the same article
+            episodes.entry(key).or_insert(episode);
+        }
+    }
+    episodes
+}
+
+/// Key podcast metadata is filed under: the item's guid, else its link,
+/// normalized the way feed-rs normalizes entry links
+pub fn podcast_key(guid: &str, link: Option<&str>) -> Option<String> {
+    let guid = guid.trim();
+    if !guid.is_empty() {
+        return Some(guid.to_string());
+    }
+
+    link.map(|link| {
+        Url::parse(link.trim())
+            .map(String::from)
+            .unwrap_or_else(|_| link.trim().to_string())
+    })
+}
+
+fn push_key_text(item: &mut PodcastItem, field: &[u8], text: &str) {
+    match field {
+        b"guid" => item.guid.push_str(text),
+        _ => item.link.push_str(text),
+    }
+}
+
+fn image_href(element: &BytesStart, decoder: Decoder) -> Option<String> {
+    element
+        .try_get_attribute("href")
+        .ok()
+        .flatten()
+        .and_then(|href| {
+            href.decoded_and_normalized_value(XmlVersion::default(), decoder)
+                .ok()
+                .map(|v| v.trim().to_string())
+        })
+        .filter(|href| !href.is_empty())
+}
+
+fn set_episode_field(episode: &mut PodcastEpisode, field: &[u8], text: &str) {
+    match field {
+        b"episode" => episode.episode = text.parse().ok(),
+        b"season" => episode.season = text.parse().ok(),
+        b"duration" => episode.duration_secs = parse_duration(text),
+        b"explicit" => {
+            episode.explicit = match text.to_ascii_lowercase().as_str() {
+                "yes" | "true" | "explicit" => Some(true),
+                "no" | "false" | "clean" => Some(false),
+                _ => None,
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Seconds in an `itunes:duration`, written as seconds, `MM:SS` or `HH:MM:SS`
+fn parse_duration(text: &str) -> Option<i64> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    parts
+        .iter()
+        .try_fold(0.0, |secs, part| {
+            let value = part.trim().parse::<f64>().ok().filter(|v| *v >= 0.0)?;
+            Some(secs * 60.0 + value)
+        })
+        .map(|secs| secs as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_poll_hints() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/">
+            <channel>
+                <title>Example</title>
+                <ttl>60</ttl>
+                <sy:updatePeriod>daily</sy:updatePeriod>
+                <sy:updateFrequency>4</sy:updateFrequency>
+                <skipHours><hour>0</hour><hour>24</hour><hour>3</hour></skipHours>
+                <skipDays><day>Saturday</day><day>Sunday</day></skipDays>
+                <item><title>Not a hint</title><hour>5</hour></item>
+            </channel>
+        </rss>"#;
+
+        let hints = parse_poll_hints(rss);
+        assert_eq!(hints.update_period_secs, Some(6 * 60 * 60));
+        assert_eq!(hints.skip_hours, vec![0, 0, 3]);
+        assert_eq!(hints.skip_days, vec![Weekday::Sat, Weekday::Sun]);
+    }
+
+    #[test]
+    fn test_parse_poll_hints_without_hints() {
+        let hints = parse_poll_hints(br#"{"version": "https://jsonfeed.org/version/1.1"}"#);
+        assert!(hints.update_period_secs.is_none());
+        assert!(hints.skip_hours.is_empty());
+        assert!(hints.skip_days.is_empty());
+    }
+
+    #[test]
+    fn test_parse_podcast_episodes() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"
+             xmlns:other="https://example.com/other">
+            <channel>
+                <title>Podcast</title>
+                <itunes:explicit>no</itunes:explicit>
+                <itunes:image href="https://example.com/show.jpg"/>
+                <other:item><itunes:episode>9</itunes:episode></other:item>
+                <item>
+                    <title>Pilot</title>
+                    <guid isPermaLink="false">pilot&amp;1</guid>
+                    <itunes:episode>1</itunes:episode>
+                    <itunes:season>2</itunes:season>
+                    <itunes:duration>1:02:03</itunes:duration>
+                    <itunes:explicit>true</itunes:explicit>
+                    <itunes:image href="https://example.com/1.jpg"/>
+                </item>
+                <item>
+                    <title>Bonus</title>
+                    <link>https://example.com</link>
+                    <itunes:duration>95</itunes:duration>
+                </item>
+                <item>
+                    <title>Unkeyed</title>
+                    <itunes:duration>60</itunes:duration>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let episodes = parse_podcast_episodes(rss);
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(
+            episodes.get("pilot&1"),
+            Some(&PodcastEpisode {
+                episode: Some(1),
+                season: Some(2),
+                duration_secs: Some(3723),
+                explicit: Some(true),
+                image_url: Some("https://example.com/1.jpg".to_string()),
+            })
+        );
+
+        // Show-level explicit flag and artwork apply to the whole feed
+        let key = podcast_key("", Some("https://example.com")).unwrap();
+        let bonus = &episodes[&key];
+        assert_eq!(bonus.duration_secs, Some(95));
+        assert_eq!(bonus.explicit, Some(false));
+        assert_eq!(
+            bonus.image_url.as_deref(),
+            Some("https://example.com/show.jpg")
+        );
+
+        let plain = br#"<rss version="2.0"><channel><title>Blog</title><item><guid>1</guid><title>Post</title></item></channel></rss>"#;
+        assert!(parse_podcast_episodes(plain).is_empty());
+    }
+
+    #[test]
+    fn test_podcast_key() {
+        assert_eq!(
+            podcast_key(" 42 ", Some("https://example.com/1")).as_deref(),
+            Some("42")
+        );
+        // Matches the link feed-rs reports for the entry
+        assert_eq!(
+            podcast_key("", Some("https://Example.com")).as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(podcast_key("", None), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3600"), Some(3600));
+        assert_eq!(parse_duration("12:34"), Some(754));
+        assert_eq!(parse_duration("01:00:00"), Some(3600));
+        assert_eq!(parse_duration("forever"), None);
+    }
+}
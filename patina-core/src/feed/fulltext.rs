@@ -0,0 +1,265 @@
+use crate::PatinaError;
+use crate::feed::http::{create_async_client, create_client};
+use crate::feed::refresh::run_limited;
+use crate::feed::sanitize::sanitize_html;
+use crate::storage::models::RefreshOptions;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+/// Elements whose text is scored as article prose
+const SCORED_SELECTOR: &str = "p, pre, td";
+
+/// Containers that never hold the main content
+const UNLIKELY_TAGS: &[&str] = &[
+    "aside", "footer", "form", "header", "nav", "noscript", "script", "style",
+];
+
+/// class/id fragments hinting at content, or at page chrome
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "story", "text",
+];
+const NEGATIVE_HINTS: &[&str] = &[
+    "ad-", "comment", "footer", "footnote", "menu", "meta", "nav", "promo", "related", "share",
+    "sidebar", "social", "sponsor",
+];
+
+/// Paragraphs shorter than this are ignored
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Extracted content with less text than this is treated as a failure
+const MIN_CONTENT_CHARS: usize = 250;
+
+/// Fetch an article's web page and extract its main content as sanitized HTML
+pub fn fetch_full_text(url: &str) -> Result<String, PatinaError> {
+    let client = create_client()?;
+    let response = client.get(url).send()?.error_for_status()?;
+
+    // Resolve against the final URL in case the article redirected
+    let page_url = response.url().to_string();
+    let html = response.text()?;
+
+    page_content(&html, &page_url)
+}
+
+/// Fetch the full text of several articles concurrently, handing each
+/// article's id and result to `on_result` as it completes, within the limits
+/// of [`run_limited`].
+pub fn fetch_full_texts<F>(
+    articles: Vec<(i64, String)>,
+    options: &RefreshOptions,
+    mut on_result: F,
+) -> Result<(), PatinaError>
+where
+    F: FnMut(i64, Result<String, PatinaError>),
+{
+    let client = create_async_client()?;
+    let jobs = articles
+        .into_iter()
+        .map(|(article_id, url)| (url.clone(), (article_id, url)))
+        .collect();
+
+    run_limited(
+        jobs,
+        options,
+        move |(article_id, url): (i64, String)| {
+            let client = client.clone();
+            async move { (article_id, fetch_page_content(&client, &url).await) }
+        },
+        |(article_id, result)| on_result(article_id, result),
+    )
+}
+
+async fn fetch_page_content(client: &reqwest::Client, url: &str) -> Result<String, PatinaError> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    let page_url = response.url().to_string();
+    let html = response.text().await?;
+
+    page_content(&html, &page_url)
+}
+
+fn page_content(html: &str, page_url: &str) -> Result<String, PatinaError> {
+    extract_content(html, page_url)
+        .ok_or_else(|| PatinaError::ParseError("No article content found".to_string()))
+}
+
+/// Find the main content of a web page using readability-style scoring.
+///
+/// Every paragraph adds to its parent's score (and half to its grandparent)
+/// based on its length and number of commas. Candidates are weighted by
+/// class/id hints and penalised for link-heavy text, and the best one wins.
+pub fn extract_content(html: &str, page_url: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let scored = Selector::parse(SCORED_SELECTOR).unwrap();
+
+    let mut scores = HashMap::new();
+
+    for paragraph in document.select(&scored) {
+        if is_unlikely(&paragraph) {
+            continue;
+        }
+
+        let text = text_of(&paragraph);
+        let chars = text.chars().count();
+        if chars < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+
+        let score = 1.0 + text.matches(',').count() as f64 + (chars / 100).min(3) as f64;
+
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent.and_then(|p| p.parent()).and_then(ElementRef::wrap);
+
+        for (candidate, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            if let Some(candidate) = candidate {
+                *scores
+                    .entry(candidate.id())
+                    .or_insert_with(|| class_weight(&candidate)) += score * share;
+            }
+        }
+    }
+
+    let best = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((element, score * (1.0 - link_density(&element))))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(element, _)| element)?;
+
+    if text_of(&best).chars().count() < MIN_CONTENT_CHARS {
+        return None;
+    }
+
+    Some(sanitize_html(&best.inner_html(), Some(page_url)))
+}
+
+/// Whether an element sits inside page chrome such as navigation or comments
+fn is_unlikely(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| {
+            UNLIKELY_TAGS.contains(&ancestor.value().name()) || class_weight(&ancestor) < 0.0
+        })
+}
+
+/// Starting score of a candidate from its class and id
+fn class_weight(element: &ElementRef) -> f64 {
+    let value = element.value();
+    let hints = format!(
+        "{} {}",
+        value.attr("class").unwrap_or_default(),
+        value.attr("id").unwrap_or_default()
+    )
+    .to_lowercase();
+
+    let mut weight = 0.0;
+    if NEGATIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight += 25.0;
+    }
+    if value.name() == "article" {
+        weight += 10.0;
+    }
+
+    weight
+}
+
+/// Share of an element's text that is link text
+fn link_density(element: &ElementRef) -> f64 {
+    let links = Selector::parse("a").unwrap();
+
+    let total = text_of(element).chars().count();
+    if total == 0 {
+        return 1.0;
+    }
+
+    let linked: usize = element
+        .select(&links)
+        .map(|link| text_of(&link).chars().count())
+        .sum();
+
+    linked as f64 / total as f64
+}
+
+/// Whitespace-normalised text of an element
+fn text_of(element: &ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_server::serve_pages;
+
+    #[test]
+    fn test_extract_blog_post() {
+        let html = include_str!("../../tests/fixtures/fulltext/blog_post.html");
+        let content = extract_content(html, "https://blog.example.com/2024/01/rust-tips").unwrap();
+
+        assert!(content.contains("borrow checker"));
+        assert!(content.contains("The last tip is the simplest"));
+        // Relative images resolve against the page
+        assert!(content.contains(r#"src="https://blog.example.com/images/lifetimes.png""#));
+        // Navigation, sidebar, comments and scripts are left behind
+        assert!(!content.contains("Subscribe to the newsletter"));
+        assert!(!content.contains("Great post!"));
+        assert!(!content.contains("analytics"));
+    }
+
+    #[test]
+    fn test_extract_news_article() {
+        let html = include_str!("../../tests/fixtures/fulltext/news_article.html");
+        let content = extract_content(html, "https://news.example.org/city/bridge").unwrap();
+
+        assert!(content.contains("The council voted"));
+        assert!(content.contains("expected to reopen"));
+        assert!(!content.contains("Most read"));
+        assert!(!content.contains("Cookie preferences"));
+    }
+
+    #[test]
+    fn test_extract_nothing() {
+        let html =
+            "<html><body><nav><p>Home, About, Contact, and more links here</p></nav></body></html>";
+        assert!(extract_content(html, "https://example.com/").is_none());
+    }
+
+    #[test]
+    fn test_fetch_full_texts() {
+        let base = serve_pages(vec![
+            (
+                "/blog",
+                include_str!("../../tests/fixtures/fulltext/blog_post.html").to_string(),
+            ),
+            (
+                "/news",
+                include_str!("../../tests/fixtures/fulltext/news_article.html").to_string(),
+            ),
+        ]);
+
+        let articles = vec![
+            (1, format!("{base}/blog")),
+            (2, format!("{base}/news")),
+            (3, format!("{base}/missing")),
+        ];
+        let mut results = Vec::new();
+        fetch_full_texts(articles, &RefreshOptions::default(), |id, result| {
+            results.push((id, result))
+        })
+        .unwrap();
+        results.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].1.as_ref().unwrap().contains("borrow checker"));
+        assert!(results[1].1.is_ok());
+        assert!(matches!(results[2].1, Err(PatinaError::NetworkError(_))));
+    }
+}
@@ -8,3 +8,34 @@ pub fn create_client() -> Result<reqwest::blocking::Client, PatinaError> {
         .build()
         .map_err(Into::into)
 }
+
+/// Create an HTTP client for downloading enclosures. Large files can take
+/// much longer than a feed, so only connecting is given a time limit.
+pub fn create_download_client() -> Result<reqwest::blocking::Client, PatinaError> {
+    reqwest::blocking::Client::builder()
+        .user_agent("Patina RSS Reader/1.0")
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .timeout(None)
+        .build()
+        .map_err(Into::into)
+}
+
+/// Create a configured async HTTP client that follows redirects
+pub fn create_async_client() -> Result<reqwest::Client, PatinaError> {
+    reqwest::Client::builder()
+        .user_agent("Patina RSS Reader/1.0")
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(Into::into)
+}
+
+/// Create a configured async HTTP client for concurrent feed refresh.
+/// Redirects are left to the caller so permanent moves can be recorded.
+pub fn create_refresh_client() -> Result<reqwest::Client, PatinaError> {
+    reqwest::Client::builder()
+        .user_agent("Patina RSS Reader/1.0")
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(Into::into)
+}
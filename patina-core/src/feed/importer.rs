@@ -0,0 +1,464 @@
+use crate::PatinaError;
+use crate::feed::parser::clean_html;
+use crate::feed::sanitize::sanitize_html;
+use crate::storage::models::{
+    ExportedItem, OpmlFeed, ParsedArticle, ReaderExport, ReaderExportFormat,
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Google Reader stream tags for item state, after the `user/<id>` prefix
+const READ_STATE: &str = "/state/com.google/read";
+const STARRED_STATE: &str = "/state/com.google/starred";
+
+/// Read another feed reader's export, telling the format from its shape
+pub fn parse_reader_export(content: &str) -> Result<ReaderExport, PatinaError> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| PatinaError::ParseError(e.to_string()))?;
+
+    match &value {
+        Value::Array(_) => parse_feedbin(value),
+        Value::Object(object) if object.contains_key("items") => parse_google_reader(value),
+        Value::Object(object) if object.contains_key("entries") => parse_miniflux(value),
+        _ => Err(PatinaError::ParseError(
+            "Unrecognized feed reader export".to_string(),
+        )),
+    }
+}
+
+fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, PatinaError> {
+    serde_json::from_value(value).map_err(|e| PatinaError::ParseError(e.to_string()))
+}
+
+/// Normalize a feed URL the way subscriptions are stored, if it is a web URL
+fn feed_url(url: &str) -> Option<String> {
+    let url = url::Url::parse(url.trim()).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Add a feed unless it is already listed
+fn push_feed(feeds: &mut Vec<OpmlFeed>, feed: OpmlFeed) {
+    if !feeds.iter().any(|listed| listed.url == feed.url) {
+        feeds.push(feed);
+    }
+}
+
+/// An exported article, keyed by its URL so the feed's own guid replaces it
+/// when the feed is next fetched
+fn exported_article(
+    title: Option<String>,
+    url: String,
+    summary_html: Option<String>,
+    content_html: Option<String>,
+    published_at: Option<i64>,
+    authors: Vec<String>,
+    categories: Vec<String>,
+) -> ParsedArticle {
+    let summary_html = summary_html.filter(|html| !html.trim().is_empty());
+    let content_html = content_html.filter(|html| !html.trim().is_empty());
+
+    let summary = summary_html
+        .as_deref()
+        .or(content_html.as_deref())
+        .map(clean_html);
+    let content_html = content_html
+        .or(summary_html)
+        .map(|html| sanitize_html(&html, Some(&url)));
+
+    ParsedArticle {
+        guid: String::new(),
+        title: title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| "Untitled".to_string()),
+        url,
+        summary,
+        content_html,
+        published_at,
+        authors: authors
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        categories,
+        ..Default::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct GoogleReaderStream {
+    /// `user/<id>/state/com.google/starred` for a starred.json export
+    #[serde(default)]
+    id: String,
+    items: Vec<GoogleReaderItem>,
+}
+
+#[derive(Deserialize)]
+struct GoogleReaderItem {
+    title: Option<String>,
+    published: Option<i64>,
+    #[serde(default)]
+    canonical: Vec<GoogleReaderLink>,
+    #[serde(default)]
+    alternate: Vec<GoogleReaderLink>,
+    summary: Option<GoogleReaderContent>,
+    content: Option<GoogleReaderContent>,
+    author: Option<String>,
+    #[serde(default)]
+    categories: Vec<String>,
+    origin: Option<GoogleReaderOrigin>,
+}
+
+#[derive(Deserialize)]
+struct GoogleReaderLink {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleReaderContent {
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleReaderOrigin {
+    /// `feed/<url>`; FreshRSS uses `feed/<id>` and adds `feedUrl`
+    stream_id: String,
+    feed_url: Option<String>,
+    title: Option<String>,
+    html_url: Option<String>,
+}
+
+/// Google Reader stream JSON: Google Takeout, Inoreader and FreshRSS exports
+fn parse_google_reader(value: Value) -> Result<ReaderExport, PatinaError> {
+    let stream: GoogleReaderStream = from_value(value)?;
+    let starred_stream = stream.id.ends_with(STARRED_STATE);
+
+    let mut export = ReaderExport {
+        format: ReaderExportFormat::GoogleReader,
+        feeds: Vec::new(),
+        items: Vec::new(),
+    };
+
+    for item in stream.items {
+        let Some(origin) = item.origin else {
+            continue;
+        };
+        let Some(url) = origin
+            .feed_url
+            .as_deref()
+            .or(origin.stream_id.strip_prefix("feed/"))
+            .and_then(feed_url)
+        else {
+            continue;
+        };
+        push_feed(
+            &mut export.feeds,
+            OpmlFeed {
+                url: url.clone(),
+                title: origin.title,
+                site_url: origin.html_url,
+                folder: Vec::new(),
+            },
+        );
+
+        // Link-less items can't be told apart once stored, so they are skipped
+        let Some(link) = item
+            .canonical
+            .into_iter()
+            .chain(item.alternate)
+            .find(|link| !link.href.is_empty())
+        else {
+            continue;
+        };
+        let has_state = |state: &str| item.categories.iter().any(|c| c.ends_with(state));
+        let is_read = has_state(READ_STATE);
+        let starred = starred_stream || has_state(STARRED_STATE);
+
+        // Reader-specific labels and states all live under `user/`
+        let categories = item
+            .categories
+            .iter()
+            .filter(|category| !category.starts_with("user/"))
+            .cloned()
+            .collect();
+
+        export.items.push(ExportedItem {
+            feed_url: url,
+            article: exported_article(
+                item.title,
+                link.href,
+                item.summary.map(|s| s.content),
+                item.content.map(|c| c.content),
+                item.published,
+                item.author.into_iter().collect(),
+                categories,
+            ),
+            is_read,
+            starred,
+        });
+    }
+
+    Ok(export)
+}
+
+#[derive(Deserialize)]
+struct MinifluxEntries {
+    entries: Vec<MinifluxEntry>,
+}
+
+#[derive(Deserialize)]
+struct MinifluxEntry {
+    title: Option<String>,
+    url: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    author: String,
+    published_at: Option<String>,
+    /// `read`, `unread` or `removed`
+    status: String,
+    #[serde(default)]
+    starred: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+    feed: MinifluxFeed,
+}
+
+#[derive(Deserialize)]
+struct MinifluxFeed {
+    title: Option<String>,
+    feed_url: String,
+    site_url: Option<String>,
+    category: Option<MinifluxCategory>,
+}
+
+#[derive(Deserialize)]
+struct MinifluxCategory {
+    title: String,
+}
+
+/// Miniflux entries JSON, as returned by its `/v1/entries` API
+fn parse_miniflux(value: Value) -> Result<ReaderExport, PatinaError> {
+    let entries: MinifluxEntries = from_value(value)?;
+
+    let mut export = ReaderExport {
+        format: ReaderExportFormat::Miniflux,
+        feeds: Vec::new(),
+        items: Vec::new(),
+    };
+
+    for entry in entries.entries {
+        let Some(url) = feed_url(&entry.feed.feed_url) else {
+            continue;
+        };
+        push_feed(
+            &mut export.feeds,
+            OpmlFeed {
+                url: url.clone(),
+                title: entry.feed.title,
+                site_url: entry.feed.site_url.filter(|url| !url.is_empty()),
+                folder: entry.feed.category.map(|c| c.title).into_iter().collect(),
+            },
+        );
+
+        if entry.status == "removed" || entry.url.is_empty() {
+            continue;
+        }
+
+        let published_at = entry
+            .published_at
+            .and_then(|date| chrono::DateTime::parse_from_rfc3339(&date).ok())
+            .map(|date| date.timestamp());
+
+        export.items.push(ExportedItem {
+            feed_url: url,
+            article: exported_article(
+                entry.title,
+                entry.url,
+                None,
+                Some(entry.content),
+                published_at,
+                vec![entry.author],
+                entry.tags,
+            ),
+            is_read: entry.status == "read",
+            starred: entry.starred,
+        });
+    }
+
+    Ok(export)
+}
+
+#[derive(Deserialize)]
+struct FeedbinSubscription {
+    title: Option<String>,
+    feed_url: String,
+    site_url: Option<String>,
+}
+
+/// Feedbin subscriptions JSON; it carries no articles
+fn parse_feedbin(value: Value) -> Result<ReaderExport, PatinaError> {
+    let subscriptions: Vec<FeedbinSubscription> = from_value(value)?;
+
+    let mut export = ReaderExport {
+        format: ReaderExportFormat::Feedbin,
+        feeds: Vec::new(),
+        items: Vec::new(),
+    };
+
+    for subscription in subscriptions {
+        if let Some(url) = feed_url(&subscription.feed_url) {
+            push_feed(
+                &mut export.feeds,
+                OpmlFeed {
+                    url,
+                    title: subscription.title,
+                    site_url: subscription.site_url,
+                    folder: Vec::new(),
+                },
+            );
+        }
+    }
+
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_google_reader_starred() {
+        let json = include_str!("../../tests/fixtures/importers/google_reader_starred.json");
+        let export = parse_reader_export(json).unwrap();
+        assert_eq!(export.format, ReaderExportFormat::GoogleReader);
+
+        let urls: Vec<_> = export.feeds.iter().map(|f| f.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://blog.example.com/feed.xml",
+                "https://news.example.org/rss"
+            ]
+        );
+        assert_eq!(export.feeds[0].title.as_deref(), Some("Example Blog"));
+        assert_eq!(
+            export.feeds[0].site_url.as_deref(),
+            Some("https://blog.example.com/")
+        );
+
+        // Everything in starred.json is starred; read state comes from the item
+        assert_eq!(export.items.len(), 3);
+        assert!(export.items.iter().all(|item| item.starred));
+        let read: Vec<_> = export.items.iter().map(|item| item.is_read).collect();
+        assert_eq!(read, vec![true, false, true]);
+
+        let first = &export.items[0];
+        assert_eq!(first.feed_url, "https://blog.example.com/feed.xml");
+        assert_eq!(first.article.url, "https://blog.example.com/2013/reader");
+        assert_eq!(first.article.title, "Farewell, Reader");
+        assert_eq!(first.article.published_at, Some(1_372_636_800));
+        assert_eq!(first.article.authors, vec!["Jane Doe"]);
+        assert_eq!(first.article.categories, vec!["rss"]);
+        assert_eq!(first.article.summary.as_deref(), Some("It was a good run."));
+        assert!(first.article.guid.is_empty());
+
+        // The canonical link wins over the alternate one
+        assert_eq!(
+            export.items[1].article.url,
+            "https://news.example.org/story/42"
+        );
+    }
+
+    #[test]
+    fn test_parse_freshrss_export() {
+        let json = include_str!("../../tests/fixtures/importers/freshrss_reading_list.json");
+        let export = parse_reader_export(json).unwrap();
+        assert_eq!(export.format, ReaderExportFormat::GoogleReader);
+
+        // FreshRSS names feeds by id and gives the URL separately; outside a
+        // starred export, state comes from the item alone
+        assert_eq!(export.feeds.len(), 1);
+        assert_eq!(export.feeds[0].url, "https://freshrss.example.net/atom.xml");
+        assert_eq!(export.items.len(), 2);
+        assert!(export.items[0].starred && !export.items[0].is_read);
+        assert!(!export.items[1].starred && export.items[1].is_read);
+        assert_eq!(
+            export.items[1].article.content_html.as_deref(),
+            Some("<p>Second <em>post</em></p>")
+        );
+    }
+
+    #[test]
+    fn test_parse_google_reader_linkless() {
+        let json = include_str!("../../tests/fixtures/importers/google_reader_linkless.json");
+        let export = parse_reader_export(json).unwrap();
+
+        // The feed is still subscribed, but only the linked item is carried over
+        assert_eq!(export.feeds.len(), 1);
+        assert_eq!(export.items.len(), 1);
+        assert_eq!(
+            export.items[0].article.url,
+            "https://status.example.com/incidents/1"
+        );
+        assert!(export.items[0].starred && !export.items[0].is_read);
+    }
+
+    #[test]
+    fn test_parse_miniflux_entries() {
+        let json = include_str!("../../tests/fixtures/importers/miniflux_entries.json");
+        let export = parse_reader_export(json).unwrap();
+        assert_eq!(export.format, ReaderExportFormat::Miniflux);
+
+        assert_eq!(export.feeds.len(), 2);
+        assert_eq!(export.feeds[0].url, "https://lwn.example.net/headlines/rss");
+        assert_eq!(export.feeds[0].folder, vec!["Linux"]);
+        assert_eq!(
+            export.feeds[1].site_url.as_deref(),
+            Some("https://rust.example.org/")
+        );
+
+        // Removed entries are skipped, but their feed is still subscribed
+        assert_eq!(export.items.len(), 2);
+        let state: Vec<_> = export
+            .items
+            .iter()
+            .map(|item| (item.is_read, item.starred))
+            .collect();
+        assert_eq!(state, vec![(true, true), (false, false)]);
+        assert_eq!(export.items[0].article.published_at, Some(1_700_000_000));
+        assert_eq!(export.items[0].article.categories, vec!["kernel"]);
+        assert!(export.items[1].article.authors.is_empty());
+    }
+
+    #[test]
+    fn test_parse_feedbin_subscriptions() {
+        let json = include_str!("../../tests/fixtures/importers/feedbin_subscriptions.json");
+        let export = parse_reader_export(json).unwrap();
+        assert_eq!(export.format, ReaderExportFormat::Feedbin);
+        assert!(export.items.is_empty());
+
+        let feeds: Vec<_> = export
+            .feeds
+            .iter()
+            .map(|f| (f.url.as_str(), f.title.as_deref()))
+            .collect();
+        assert_eq!(
+            feeds,
+            vec![
+                (
+                    "https://daringfireball.net/feeds/main",
+                    Some("Daring Fireball")
+                ),
+                ("https://xkcd.com/atom.xml", Some("xkcd"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_export() {
+        assert!(parse_reader_export(r#"{"feeds": []}"#).is_err());
+        assert!(parse_reader_export("<opml/>").is_err());
+    }
+}
@@ -1,4 +1,17 @@
+pub mod backfill;
 pub mod discovery;
+pub mod download;
+pub mod extensions;
+pub mod fulltext;
 pub mod http;
+pub mod importer;
 pub mod opml;
 pub mod parser;
+pub mod refresh;
+pub mod sanitize;
+pub mod schedule;
+
+#[cfg(test)]
+pub mod test_db;
+#[cfg(test)]
+mod test_server;
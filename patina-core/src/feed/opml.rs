@@ -1,18 +1,40 @@
 use crate::PatinaError;
 use crate::storage::models::OpmlFeed;
 
+/// Receives progress while an OPML import fetches its feeds
+#[uniffi::export(with_foreign)]
+pub trait ImportProgress: Send + Sync {
+    /// Called once per entry as it is done with. The counts so far are those
+    /// of the final `OpmlImportResult`: `pending` feeds are subscribed but
+    /// couldn't be fetched yet, `skipped` ones were already subscribed or
+    /// listed twice, and `failed` ones weren't subscribed.
+    fn on_progress(
+        &self,
+        imported: u32,
+        pending: u32,
+        skipped: u32,
+        failed: u32,
+        total: u32,
+        current_url: String,
+    );
+}
+
 /// Parse an OPML file and extract feed URLs
 pub fn parse_opml(content: &str) -> Result<Vec<OpmlFeed>, PatinaError> {
     let opml = opml::OPML::from_str(content).map_err(|e| PatinaError::ParseError(e.to_string()))?;
 
     let mut feeds = Vec::new();
-    extract_feeds_recursive(&opml.body.outlines, &mut feeds);
+    extract_feeds_recursive(&opml.body.outlines, &mut Vec::new(), &mut feeds);
 
     Ok(feeds)
 }
 
 /// Recursively extract feeds from OPML outline structure
-fn extract_feeds_recursive(outlines: &[opml::Outline], feeds: &mut Vec<OpmlFeed>) {
+fn extract_feeds_recursive(
+    outlines: &[opml::Outline],
+    folder: &mut Vec<String>,
+    feeds: &mut Vec<OpmlFeed>,
+) {
     for outline in outlines {
         // Check if this is a feed (has xmlUrl)
         if let Some(xml_url) = &outline.xml_url {
@@ -27,13 +49,74 @@ fn extract_feeds_recursive(outlines: &[opml::Outline], feeds: &mut Vec<OpmlFeed>
                 feeds.push(OpmlFeed {
                     url: xml_url.clone(),
                     title,
+                    site_url: outline.html_url.clone().filter(|url| !url.is_empty()),
+                    folder: folder.clone(),
                 });
             }
+            continue;
         }
 
         // Recurse into child outlines (folders)
-        extract_feeds_recursive(&outline.outlines, feeds);
+        let name = if !outline.text.is_empty() {
+            outline.text.clone()
+        } else {
+            outline.title.clone().unwrap_or_default()
+        };
+        folder.push(name);
+        extract_feeds_recursive(&outline.outlines, folder, feeds);
+        folder.pop();
+    }
+}
+
+/// Write feeds as an OPML 2.0 document, nested in folder outlines.
+/// `parse_opml` reads the feeds back as they were given, grouped by folder.
+pub fn write_opml(
+    title: &str,
+    feeds: &[OpmlFeed],
+    date_created: i64,
+) -> Result<String, PatinaError> {
+    let date_created =
+        chrono::DateTime::from_timestamp(date_created, 0).map(|date| date.to_rfc2822());
+
+    let mut opml = opml::OPML {
+        version: "2.0".to_string(),
+        head: Some(opml::Head {
+            title: Some(title.to_string()),
+            date_created,
+            ..opml::Head::default()
+        }),
+        ..opml::OPML::default()
+    };
+
+    for feed in feeds {
+        let mut outlines = &mut opml.body.outlines;
+        for name in &feed.folder {
+            let position = outlines
+                .iter()
+                .position(|outline| outline.xml_url.is_none() && outline.text == *name);
+            let position = position.unwrap_or_else(|| {
+                outlines.push(opml::Outline {
+                    text: name.clone(),
+                    title: Some(name.clone()),
+                    ..opml::Outline::default()
+                });
+                outlines.len() - 1
+            });
+            outlines = &mut outlines[position].outlines;
+        }
+
+        outlines.push(opml::Outline {
+            text: feed.title.clone().unwrap_or_default(),
+            title: feed.title.clone(),
+            r#type: Some("rss".to_string()),
+            xml_url: Some(feed.url.clone()),
+            html_url: feed.site_url.clone(),
+            ..opml::Outline::default()
+        });
     }
+
+    opml.to_string()
+        .map_err(|e| PatinaError::ParseError(e.to_string()))
 }
 
 #[cfg(test)]
@@ -63,5 +146,33 @@ mod tests {
         assert_eq!(feeds.len(), 2);
         assert!(feeds.iter().any(|f| f.url.contains("ycombinator")));
         assert!(feeds.iter().any(|f| f.url.contains("example.com")));
+
+        assert_eq!(feeds[0].folder, vec!["Tech"]);
+        assert_eq!(
+            feeds[0].site_url.as_deref(),
+            Some("https://news.ycombinator.com")
+        );
+        assert!(feeds[1].folder.is_empty());
+    }
+
+    #[test]
+    fn test_write_opml_round_trips() {
+        let feed = |url: &str, title: Option<&str>, folder: &[&str]| OpmlFeed {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            site_url: Some(format!("{}/", url.trim_end_matches("/feed"))),
+            folder: folder.iter().map(|name| name.to_string()).collect(),
+        };
+        let feeds = vec![
+            feed("https://a.example/feed", Some("Tom & Jerry's <News>"), &[]),
+            feed("https://b.example/feed", Some("B"), &["Tech", "Rust"]),
+            feed("https://c.example/feed", None, &["Tech", "Rust"]),
+            feed("https://d.example/feed", Some("D"), &["Tech"]),
+            feed("https://e.example/feed", Some("E"), &["Café"]),
+        ];
+
+        let document = write_opml("Subscriptions", &feeds, 1_700_000_000).unwrap();
+        assert!(document.contains("<dateCreated>Tue, 14 Nov 2023 22:13:20 +0000</dateCreated>"));
+        assert_eq!(parse_opml(&document).unwrap(), feeds);
     }
 }
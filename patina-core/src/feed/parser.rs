@@ -1,54 +1,417 @@
 use crate::PatinaError;
+use crate::feed::extensions::{parse_podcast_episodes, parse_poll_hints, podcast_key};
 use crate::feed::http::create_client;
-use crate::storage::models::{ParsedArticle, ParsedFeed};
+use crate::feed::sanitize::sanitize_html;
+use crate::storage::models::{
+    FeedFormat, FeedValidators, ParsedArticle, ParsedEnclosure, ParsedFeed, PollHints,
+};
+use feed_rs::model::{FeedType, Link, MediaObject, Person};
 use feed_rs::parser;
+use reqwest::StatusCode;
+use reqwest::header::{
+    CACHE_CONTROL, CONTENT_TYPE, ETAG, EXPIRES, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, LOCATION,
+};
+use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Redirect hops followed before giving up
+const MAX_REDIRECTS: usize = 10;
+
+/// Outcome of a conditional feed fetch
+#[derive(Debug)]
+pub enum FetchResult {
+    /// The server answered 304 Not Modified, stored articles are current
+    NotModified,
+    /// The feed was downloaded and parsed
+    Modified(Box<ParsedFeed>),
+    /// The server answered 410 Gone, the feed has been removed for good
+    Gone,
+}
+
+/// What a URL given as a feed turned out to serve
+#[derive(Debug)]
+pub enum FetchedDocument {
+    Feed(Box<ParsedFeed>),
+    /// A web page, to look for feeds in
+    Page {
+        html: String,
+        url: url::Url,
+    },
+}
+
+/// A fetch result together with the response metadata refresh needs
+#[derive(Debug)]
+pub struct FeedResponse {
+    /// HTTP status, `None` if no response was received
+    pub http_status: Option<u16>,
+    /// Freshness lifetime from `Cache-Control: max-age` or `Expires`, in seconds
+    pub max_age_secs: Option<i64>,
+    /// New feed URL if the request was permanently redirected (301/308)
+    pub moved_to: Option<String>,
+    pub result: Result<FetchResult, PatinaError>,
+}
 
 /// Fetch a feed from a URL and parse it
 pub fn fetch_and_parse_feed(url: &str) -> Result<ParsedFeed, PatinaError> {
     let client = create_client()?;
-    let response = client.get(url).send()?;
+    let response = client.get(url).send()?.error_for_status()?;
+    let final_url = response.url().to_string();
+    let validators = cache_validators(response.headers());
+    let bytes = response.bytes()?;
+
+    let mut feed = parse_fetched_feed(&bytes, url, &final_url)?;
+    feed.etag = validators.etag;
+    feed.last_modified = validators.last_modified;
+
+    Ok(feed)
+}
+
+/// Fetch a URL that is either a feed or a web page, telling them apart by
+/// trying the body as a feed first and falling back to HTML when it looks like a page
+pub fn fetch_feed_or_page(url: &str) -> Result<FetchedDocument, PatinaError> {
+    let client = create_client()?;
+    let response = client.get(url).send()?.error_for_status()?;
+    let page_url = response.url().clone();
+    let html_content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_ascii_lowercase().contains("html"));
+    let validators = cache_validators(response.headers());
     let bytes = response.bytes()?;
 
-    parse_feed_content(&bytes, url)
+    match parse_fetched_feed(&bytes, url, page_url.as_str()) {
+        Ok(mut feed) => {
+            feed.etag = validators.etag;
+            feed.last_modified = validators.last_modified;
+            Ok(FetchedDocument::Feed(Box::new(feed)))
+        }
+        Err(_) if html_content_type || looks_like_html(&bytes) => Ok(FetchedDocument::Page {
+            html: String::from_utf8_lossy(&bytes).into_owned(),
+            url: page_url,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether a document starts like an HTML page
+fn looks_like_html(content: &[u8]) -> bool {
+    let head = &content[..content.len().min(1024)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+
+    head.starts_with("<!doctype html") || head.starts_with("<html") || head.contains("<head")
+}
+
+/// Fetch a feed, sending the stored validators as a conditional GET.
+///
+/// Expects a client that doesn't follow redirects itself: they are followed
+/// here so a chain of permanent redirects can be reported in `moved_to`.
+pub async fn fetch_feed(
+    client: &reqwest::Client,
+    url: &str,
+    validators: &FeedValidators,
+) -> FeedResponse {
+    let mut current = url.to_string();
+    let mut moved_to = None;
+    let mut permanent = true;
+    let origin = url::Url::parse(url).ok().map(|url| url.origin());
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut request = client.get(&current);
+
+        // The validators were issued by the feed's own server; another origin
+        // could answer 304 for a document it never sent
+        if url::Url::parse(&current).ok().map(|url| url.origin()) == origin {
+            if let Some(etag) = &validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return failed_response(None, moved_to, e.into()),
+        };
+
+        let status = response.status();
+        if status.is_redirection() && status != StatusCode::NOT_MODIFIED {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|location| url::Url::parse(&current).ok()?.join(location).ok());
+
+            let Some(location) = location else {
+                let error = PatinaError::NetworkError(format!("HTTP {} without Location", status));
+                return failed_response(Some(status.as_u16()), moved_to, error);
+            };
+
+            // Only a chain of permanent redirects moves the subscription
+            permanent &=
+                status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::PERMANENT_REDIRECT;
+            if permanent {
+                moved_to = Some(location.to_string());
+            }
+
+            current = location.to_string();
+            continue;
+        }
+
+        return FeedResponse {
+            http_status: Some(status.as_u16()),
+            max_age_secs: cache_max_age(response.headers(), chrono::Utc::now().timestamp()),
+            moved_to,
+            result: read_feed_response(response, url, &current).await,
+        };
+    }
+
+    let error = PatinaError::NetworkError("Too many redirects".to_string());
+    failed_response(None, moved_to, error)
+}
+
+/// A response for a fetch that failed before a feed could be read
+fn failed_response(
+    http_status: Option<u16>,
+    moved_to: Option<String>,
+    error: PatinaError,
+) -> FeedResponse {
+    FeedResponse {
+        http_status,
+        max_age_secs: None,
+        moved_to,
+        result: Err(error),
+    }
+}
+
+/// Turn a feed response into a fetch result. `final_url` is where the
+/// response came from after redirects.
+async fn read_feed_response(
+    response: reqwest::Response,
+    url: &str,
+    final_url: &str,
+) -> Result<FetchResult, PatinaError> {
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(FetchResult::NotModified);
+    }
+    if status == StatusCode::GONE {
+        return Ok(FetchResult::Gone);
+    }
+    if !status.is_success() {
+        return Err(PatinaError::NetworkError(format!("HTTP {}", status)));
+    }
+
+    let validators = cache_validators(response.headers());
+    let bytes = response.bytes().await?;
+
+    let mut feed = parse_fetched_feed(&bytes, url, final_url)?;
+    feed.etag = validators.etag;
+    feed.last_modified = validators.last_modified;
+
+    Ok(FetchResult::Modified(Box::new(feed)))
+}
+
+/// Extract the ETag and Last-Modified validators from response headers
+fn cache_validators(headers: &HeaderMap) -> FeedValidators {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    FeedValidators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    }
+}
+
+/// Freshness lifetime of a response in seconds, from `Cache-Control` or `Expires`
+fn cache_max_age(headers: &HeaderMap, now: i64) -> Option<i64> {
+    let cache_control = headers.get(CACHE_CONTROL).and_then(|v| v.to_str().ok());
+
+    if let Some(cache_control) = cache_control {
+        for directive in cache_control
+            .split(',')
+            .map(|d| d.trim().to_ascii_lowercase())
+        {
+            if directive == "no-cache" || directive == "no-store" {
+                return Some(0);
+            }
+            if let Some(secs) = directive.strip_prefix("max-age=") {
+                if let Ok(secs) = secs.trim_matches('"').parse::<i64>() {
+                    return Some(secs.max(0));
+                }
+            }
+        }
+    }
+
+    // Expires is ignored when max-age is present
+    headers
+        .get(EXPIRES)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+        .map(|expires| (expires.timestamp() - now).max(0))
+}
+
+/// Parse a downloaded feed. Relative links resolve against `final_url`, where
+/// the document was served after redirects, but the feed keeps the URL it was
+/// requested at.
+fn parse_fetched_feed(
+    content: &[u8],
+    url: &str,
+    final_url: &str,
+) -> Result<ParsedFeed, PatinaError> {
+    let mut feed = parse_feed_content(content, final_url)?;
+    feed.url = url.to_string();
+    Ok(feed)
 }
 
 /// Parse feed content from bytes
 pub fn parse_feed_content(content: &[u8], url: &str) -> Result<ParsedFeed, PatinaError> {
-    let feed = parser::parse(content).map_err(|e| PatinaError::ParseError(e.to_string()))?;
+    // Leave missing ids empty rather than letting feed-rs invent random ones,
+    // so entries without a guid fall back to a stable identity of our own
+    let feed = parser::Builder::new()
+        .id_generator(|_, _, _| String::new())
+        .build()
+        .parse(content)
+        .map_err(|e| PatinaError::ParseError(e.to_string()))?;
+    let format = feed_format(&feed.feed_type);
 
     let title = feed
         .title
         .map(|t| t.content)
         .unwrap_or_else(|| "Untitled Feed".to_string());
 
-    let site_url = feed.links.first().map(|l| l.href.clone());
+    let site_url = page_link(&feed.links);
+    let next_page_url = older_entries_link(&feed.links, url);
+
+    // Atom <icon> and JSON Feed favicon are the small square images meant for
+    // this; RSS only has a logo (<image> or itunes:image)
+    let icon_url = feed
+        .icon
+        .or(feed.logo)
+        .and_then(|image| resolve_url(url, &image.uri));
+
+    // feed-rs only understands <ttl>, the other hints come from the raw document
+    let mut poll_hints = match format {
+        FeedFormat::JsonFeed => PollHints::default(),
+        _ => parse_poll_hints(content),
+    };
+    poll_hints.ttl_minutes = feed.ttl;
+
+    // Podcast tags only appear in RSS
+    let mut podcast_episodes = match format {
+        FeedFormat::Rss0 | FeedFormat::Rss2 => parse_podcast_episodes(content),
+        _ => HashMap::new(),
+    };
 
     let articles: Vec<ParsedArticle> = feed
         .entries
         .into_iter()
-        .filter_map(|entry| {
-            let entry_url = entry.links.first().map(|l| l.href.clone())?;
+        .map(|entry| {
+            let link = entry.links.first().map(|link| link.href.as_str());
+            let podcast =
+                podcast_key(&entry.id, link).and_then(|key| podcast_episodes.remove(&key));
+            let mut enclosures = entry_enclosures(&entry.media, &entry.links);
+
+            // feed-rs misreads `MM:SS` itunes:duration values, so use our own
+            let episode_duration = podcast.as_ref().and_then(|p| p.duration_secs);
+            for enclosure in &mut enclosures {
+                enclosure.duration_secs = enclosure.duration_secs.or(episode_duration);
+            }
+
+            // Entries without a link are kept; a permalink guid can stand in for it
+            let entry_url = page_link(&entry.links)
+                .or_else(|| is_web_url(&entry.id).then(|| entry.id.clone()))
+                .unwrap_or_default();
+
+            // Relative URLs in content resolve against xml:base, then the entry link
+            let base_url = entry
+                .base
+                .as_deref()
+                .and_then(|base| url::Url::parse(url).ok()?.join(base).ok())
+                .map(String::from)
+                .unwrap_or_else(|| {
+                    if entry_url.is_empty() {
+                        url.to_string()
+                    } else {
+                        entry_url.clone()
+                    }
+                });
             let entry_title = entry
                 .title
                 .map(|t| t.content)
                 .unwrap_or_else(|| "Untitled".to_string());
 
-            let summary = entry
-                .summary
-                .map(|s| s.content)
-                .or_else(|| entry.content.and_then(|c| c.body));
+            // JSON Feed only allows HTML in content_html, its summary is plain text
+            let summary_html = entry.summary.map(|s| match format {
+                FeedFormat::JsonFeed => text_to_html(&s.content),
+                _ => s.content,
+            });
+
+            // JSON Feed content_text and Atom type="text" content are plain text too
+            let content_html = entry.content.and_then(|c| {
+                let is_text = c.content_type.subty() == "plain";
+                c.body
+                    .map(|body| if is_text { text_to_html(&body) } else { body })
+            });
 
-            // Clean HTML from summary if present
-            let summary = summary.map(|s| clean_html(&s));
+            // Plain-text summary for lists and topic extraction
+            let summary = summary_html
+                .as_deref()
+                .or(content_html.as_deref())
+                .map(clean_html);
+
+            // Keep the full markup (content:encoded / Atom content) for the reader,
+            // falling back to the description when that is all the feed has.
+            // It is sanitized here so everything stored is safe to render.
+            let content_html = content_html
+                .or(summary_html)
+                .map(|html| sanitize_html(&html, Some(&base_url)));
+
+            let thumbnail_url = entry_thumbnail(&entry.media, &base_url)
+                .or_else(|| content_html.as_deref().and_then(first_image));
 
             let published_at = entry.published.or(entry.updated).map(|dt| dt.timestamp());
+            let updated_at = entry.updated.map(|dt| dt.timestamp());
+
+            let authors = entry
+                .authors
+                .into_iter()
+                .map(author_name)
+                .filter(|name| !name.is_empty())
+                .collect();
 
-            Some(ParsedArticle {
+            let categories = entry
+                .categories
+                .into_iter()
+                .map(|category| category.label.unwrap_or(category.term).trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+
+            let guid = entry_guid(&entry.id, &entry_url, &entry_title, published_at);
+
+            ParsedArticle {
+                guid,
                 title: entry_title,
                 url: entry_url,
                 summary,
+                content_html,
                 published_at,
-            })
+                updated_at,
+                authors,
+                categories,
+                enclosures,
+                podcast,
+                thumbnail_url,
+            }
         })
         .collect();
 
@@ -57,9 +420,194 @@ pub fn parse_feed_content(content: &[u8], url: &str) -> Result<ParsedFeed, Patin
         url: url.to_string(),
         site_url,
         articles,
+        poll_hints,
+        format: Some(format),
+        icon_url,
+        next_page_url,
+        ..Default::default()
     })
 }
 
+/// Identity of an entry within its feed: its id/guid, else its link, else a
+/// hash of its title and date, so the same entry is recognised on every fetch.
+/// The body is left out so an edited entry is updated rather than duplicated;
+/// articles are keyed by feed id as well, so feeds can't collide.
+fn entry_guid(id: &str, url: &str, title: &str, published_at: Option<i64>) -> String {
+    if !id.trim().is_empty() {
+        return id.trim().to_string();
+    }
+    if !url.is_empty() {
+        return url.to_string();
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(title.as_bytes());
+    hasher.update(published_at.unwrap_or_default().to_le_bytes());
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Byline of an entry author. feed-rs keeps an RSS `<author>` as a person
+/// named "author" whose email holds the element's text, "email (Name)" by
+/// convention, so the name is taken from there, else the bare email.
+fn author_name(person: Person) -> String {
+    match person.email {
+        Some(email) if person.name == "author" => match email.split_once('(') {
+            Some((address, name)) => {
+                let name = name.trim_end().trim_end_matches(')').trim();
+                let name = if name.is_empty() { address } else { name };
+                name.trim().to_string()
+            }
+            None => email.trim().to_string(),
+        },
+        _ => person.name.trim().to_string(),
+    }
+}
+
+fn is_web_url(value: &str) -> bool {
+    url::Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn feed_format(feed_type: &FeedType) -> FeedFormat {
+    match feed_type {
+        FeedType::RSS0 => FeedFormat::Rss0,
+        FeedType::RSS1 => FeedFormat::Rss1,
+        FeedType::RSS2 => FeedFormat::Rss2,
+        FeedType::Atom => FeedFormat::Atom,
+        FeedType::JSON => FeedFormat::JsonFeed,
+    }
+}
+
+/// The link to a web page: the first alternate link that isn't an attachment
+/// or a self link, falling back to the first link of any kind
+fn page_link(links: &[Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| {
+            link.rel.as_deref().is_none_or(|rel| rel == "alternate")
+                && link
+                    .media_type
+                    .as_deref()
+                    .is_none_or(|t| t.contains("html"))
+        })
+        .or(links.first())
+        .map(|link| link.href.clone())
+}
+
+/// Where a feed's older entries are: the next page of an RFC 5005 paged
+/// feed, or the previous document of an archived feed
+fn older_entries_link(links: &[Link], base: &str) -> Option<String> {
+    ["next", "prev-archive"]
+        .iter()
+        .find_map(|rel| links.iter().find(|link| link.rel.as_deref() == Some(*rel)))
+        .and_then(|link| resolve_url(base, &link.href))
+}
+
+/// Media attached to an entry: RSS `<enclosure>` and `media:content`, which
+/// feed-rs models as media objects, plus Atom `rel="enclosure"` links and
+/// JSON Feed attachments, which it keeps as links
+fn entry_enclosures(media: &[MediaObject], links: &[Link]) -> Vec<ParsedEnclosure> {
+    let mut enclosures: Vec<ParsedEnclosure> = Vec::new();
+
+    for object in media {
+        for content in &object.content {
+            let Some(url) = &content.url else {
+                continue;
+            };
+            enclosures.push(ParsedEnclosure {
+                url: url.to_string(),
+                mime_type: content.content_type.as_ref().map(|t| t.to_string()),
+                length: content.size.map(|size| size as i64),
+                duration_secs: content.duration.map(|d| d.as_secs() as i64),
+            });
+        }
+    }
+
+    for link in links {
+        let is_attachment = match link.rel.as_deref() {
+            Some(rel) => rel == "enclosure",
+            None => link
+                .media_type
+                .as_deref()
+                .is_some_and(|t| !t.contains("html")),
+        };
+        if is_attachment {
+            enclosures.push(ParsedEnclosure {
+                url: link.href.clone(),
+                mime_type: link.media_type.clone(),
+                length: link.length.map(|length| length as i64),
+                duration_secs: None,
+            });
+        }
+    }
+
+    // The same file is often listed both as an enclosure and as media:content
+    let mut seen = std::collections::HashSet::new();
+    enclosures.retain(|enclosure| seen.insert(enclosure.url.clone()));
+    enclosures
+}
+
+/// An entry's own image: a `media:thumbnail` (`media:group` included) or
+/// `itunes:image`, else an image attached as `media:content` or enclosure
+fn entry_thumbnail(media: &[MediaObject], base_url: &str) -> Option<String> {
+    let thumbnail = media
+        .iter()
+        .flat_map(|object| &object.thumbnails)
+        .map(|thumbnail| thumbnail.image.uri.as_str())
+        .next();
+
+    let attached = || {
+        media
+            .iter()
+            .flat_map(|object| &object.content)
+            .find(|content| {
+                content
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|t| t.to_string().starts_with("image/"))
+            })
+            .and_then(|content| content.url.as_ref())
+            .map(|url| url.as_str())
+    };
+
+    thumbnail
+        .or_else(attached)
+        .and_then(|uri| resolve_url(base_url, uri))
+}
+
+/// The first real image in sanitized content, skipping tracking pixels
+fn first_image(html: &str) -> Option<String> {
+    let document = Html::parse_fragment(html);
+    let selector = Selector::parse("img[src]").unwrap();
+
+    document
+        .select(&selector)
+        .find(|img| {
+            let value = img.value();
+            !(value.attr("width") == Some("1") || value.attr("height") == Some("1"))
+        })
+        .and_then(|img| img.value().attr("src"))
+        .map(str::to_string)
+}
+
+/// Resolve a possibly relative URL from a feed into an absolute web URL
+fn resolve_url(base: &str, uri: &str) -> Option<String> {
+    let url = url::Url::parse(base).ok()?.join(uri.trim()).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Turn plain text into paragraphs of escaped HTML
+fn text_to_html(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            let escaped = html_escape::encode_text(paragraph);
+            format!("<p>{}</p>", escaped.replace('\n', "<br>\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Strip HTML tags and decode entities from a string
 pub fn clean_html(html: &str) -> String {
     // Single pass: strip tags + normalize whitespace
@@ -98,6 +646,359 @@ pub fn clean_html(html: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::feed::http::create_refresh_client;
+    use crate::feed::test_server::{response, serve};
+
+    #[test]
+    fn test_fetch_feed_after_redirect() {
+        // The new host never issued the validators, so it must not see them
+        let feed_host = serve(|request| {
+            if request.header("if-none-match").is_some() {
+                return response("304 Not Modified", &[], "");
+            }
+            let rss = r#"<rss version="2.0"><channel><title>Moved</title>
+                <item><title>Relative</title><guid>1</guid>
+                <description>&lt;a href="/post"&gt;Read&lt;/a&gt;</description></item>
+            </channel></rss>"#;
+            response("200 OK", &[], rss)
+        });
+        let target = format!("{feed_host}/feed.xml");
+        let old_host = serve(move |_| {
+            response(
+                "301 Moved Permanently",
+                &[("Location", target.as_str())],
+                "",
+            )
+        });
+
+        let client = create_refresh_client().unwrap();
+        let validators = FeedValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+        let url = format!("{old_host}/feed.xml");
+        let fetched = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(fetch_feed(&client, &url, &validators));
+
+        assert_eq!(fetched.moved_to, Some(format!("{feed_host}/feed.xml")));
+        let Ok(FetchResult::Modified(feed)) = fetched.result else {
+            panic!("expected the feed, got {:?}", fetched.result.err());
+        };
+
+        // Relative links resolve against the host that served the feed
+        let content = feed.articles[0].content_html.as_deref().unwrap();
+        assert!(content.contains(&format!("{feed_host}/post")), "{content}");
+        assert_eq!(feed.url, url);
+    }
+
+    #[test]
+    fn test_fetch_error_status() {
+        // An error page is not a page offering feeds
+        let host = serve(|_| response("404 Not Found", &[], "<html><head></head></html>"));
+        let url = format!("{host}/missing");
+
+        assert!(matches!(
+            fetch_feed_or_page(&url),
+            Err(PatinaError::NetworkError(_))
+        ));
+        assert!(matches!(
+            fetch_and_parse_feed(&url),
+            Err(PatinaError::NetworkError(_))
+        ));
+    }
+
+    #[test]
+    fn test_looks_like_html() {
+        assert!(looks_like_html(
+            b"\n  <!DOCTYPE html><html><head></head></html>"
+        ));
+        assert!(looks_like_html(b"<html lang=\"en\"><body></body></html>"));
+        assert!(looks_like_html(
+            b"<?xml version=\"1.0\"?><html xmlns=\"http://www.w3.org/1999/xhtml\"><head/></html>"
+        ));
+        assert!(!looks_like_html(
+            b"<?xml version=\"1.0\"?><rss version=\"2.0\"></rss>"
+        ));
+        assert!(!looks_like_html(
+            b"{\"version\": \"https://jsonfeed.org/version/1.1\"}"
+        ));
+    }
+
+    #[test]
+    fn test_parse_article_content() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+        <channel><title>Example</title><link>https://example.com</link>
+            <item>
+                <title>Full</title><link>https://example.com/1</link>
+                <description>&lt;p&gt;Short &lt;b&gt;teaser&lt;/b&gt;&lt;/p&gt;</description>
+                <content:encoded><![CDATA[<p>The <em>whole</em> story</p>]]></content:encoded>
+            </item>
+            <item>
+                <title>Teaser only</title><link>https://example.com/2</link>
+                <description>&lt;p&gt;Just a teaser&lt;/p&gt;</description>
+            </item>
+        </channel></rss>"#;
+
+        let feed = parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+
+        let full = &feed.articles[0];
+        assert_eq!(full.summary.as_deref(), Some("Short teaser"));
+        assert_eq!(
+            full.content_html.as_deref(),
+            Some("<p>The <em>whole</em> story</p>")
+        );
+
+        let teaser = &feed.articles[1];
+        assert_eq!(teaser.summary.as_deref(), Some("Just a teaser"));
+        assert_eq!(teaser.content_html.as_deref(), Some("<p>Just a teaser</p>"));
+    }
+
+    #[test]
+    fn test_parse_json_feed() {
+        let json = br#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "JSON Example",
+            "home_page_url": "https://example.com/",
+            "feed_url": "https://example.com/feed.json",
+            "authors": [{ "name": "Feed Author" }],
+            "items": [
+                {
+                    "id": "1",
+                    "url": "https://example.com/1",
+                    "external_url": "https://elsewhere.example.net/story",
+                    "title": "Linked",
+                    "content_html": "<p>Rich <b>content</b></p>",
+                    "summary": "A <plain> summary",
+                    "date_published": "2024-01-01T10:00:00Z",
+                    "date_modified": "2024-01-02T10:00:00Z",
+                    "authors": [{ "name": "Item Author" }]
+                },
+                {
+                    "id": "2",
+                    "external_url": "https://elsewhere.example.net/other",
+                    "content_text": "First line\nsecond line\n\nNext <paragraph>",
+                    "attachments": [{ "url": "https://example.com/2.mp3", "mime_type": "audio/mpeg" }]
+                }
+            ]
+        }"#;
+
+        let feed = parse_feed_content(json, "https://example.com/feed.json").unwrap();
+        assert_eq!(feed.format, Some(FeedFormat::JsonFeed));
+        assert_eq!(feed.site_url.as_deref(), Some("https://example.com/"));
+
+        let linked = &feed.articles[0];
+        assert_eq!(linked.url, "https://example.com/1");
+        assert_eq!(linked.summary.as_deref(), Some("A <plain> summary"));
+        assert_eq!(
+            linked.content_html.as_deref(),
+            Some("<p>Rich <b>content</b></p>")
+        );
+        assert_eq!(linked.published_at, Some(1704103200));
+        assert_eq!(linked.updated_at, Some(1704189600));
+        assert_eq!(linked.authors, vec!["Item Author"]);
+
+        // Without a url the external_url is used, never the attachment
+        let text = &feed.articles[1];
+        assert_eq!(text.url, "https://elsewhere.example.net/other");
+        assert_eq!(
+            text.content_html.as_deref(),
+            Some("<p>First line<br>\nsecond line</p>\n<p>Next &lt;paragraph&gt;</p>")
+        );
+        assert_eq!(
+            text.summary.as_deref(),
+            Some("First line second line Next <paragraph>")
+        );
+        assert_eq!(text.authors, vec!["Feed Author"]);
+        assert_eq!(text.enclosures[0].url, "https://example.com/2.mp3");
+        assert_eq!(text.enclosures[0].mime_type.as_deref(), Some("audio/mpeg"));
+    }
+
+    #[test]
+    fn test_entry_identity() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0"><channel><title>Example</title><link>https://example.com</link>
+            <item><guid>tag:example.com,2024:1</guid><title>Guid</title><link>https://example.com/1</link>
+                <author>jo@example.com (Jo Bloggs)</author><category>News</category><category>Tech</category></item>
+            <item><title>Link only</title><link>https://example.com/2</link><author>jo@example.com</author></item>
+            <item><guid isPermaLink="true">https://example.com/3</guid><title>Permalink</title></item>
+            <item><title>Nothing</title><description>Just words</description></item>
+        </channel></rss>"#;
+
+        let parse = || parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+        let feed = parse();
+        assert_eq!(feed.articles.len(), 4);
+
+        assert_eq!(feed.articles[0].guid, "tag:example.com,2024:1");
+        assert_eq!(feed.articles[0].categories, vec!["News", "Tech"]);
+        assert_eq!(feed.articles[0].authors, vec!["Jo Bloggs"]);
+        assert_eq!(feed.articles[1].guid, "https://example.com/2");
+        assert_eq!(feed.articles[1].authors, vec!["jo@example.com"]);
+        assert_eq!(feed.articles[2].url, "https://example.com/3");
+
+        let hashed = &feed.articles[3];
+        assert!(hashed.guid.starts_with("sha256:"));
+        assert_eq!(hashed.url, "");
+        // The synthesized identity is the same on every parse
+        assert_eq!(parse().articles[3].guid, hashed.guid);
+    }
+
+    #[test]
+    fn test_parse_enclosures() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"
+             xmlns:media="http://search.yahoo.com/mrss/">
+        <channel><title>Podcast</title><link>https://example.com</link>
+            <item>
+                <title>Episode 1</title><link>https://example.com/1</link>
+                <enclosure url="https://cdn.example.com/1.mp3" length="1234" type="audio/mpeg"/>
+                <media:content url="https://cdn.example.com/1.mp3" type="audio/mpeg"/>
+                <itunes:duration>30:00</itunes:duration>
+                <itunes:episode>1</itunes:episode>
+            </item>
+            <item><title>Post</title><link>https://example.com/2</link></item>
+        </channel></rss>"#;
+
+        let feed = parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+
+        let episode = &feed.articles[0];
+        assert_eq!(
+            episode.enclosures,
+            vec![ParsedEnclosure {
+                url: "https://cdn.example.com/1.mp3".to_string(),
+                mime_type: Some("audio/mpeg".to_string()),
+                length: Some(1234),
+                duration_secs: Some(1800),
+            }]
+        );
+        let podcast = episode.podcast.as_ref().unwrap();
+        assert_eq!(podcast.episode, Some(1));
+        assert_eq!(podcast.duration_secs, Some(1800));
+
+        assert!(feed.articles[1].enclosures.is_empty());
+        assert!(feed.articles[1].podcast.is_none());
+
+        let atom = br#"<feed xmlns="http://www.w3.org/2005/Atom"><title>T</title><id>urn:t</id>
+            <entry><title>E</title><id>urn:e</id><updated>2024-01-01T00:00:00Z</updated>
+                <link href="https://example.com/e"/>
+                <link rel="enclosure" href="https://example.com/e.ogg" type="audio/ogg" length="99"/>
+            </entry></feed>"#;
+        let feed = parse_feed_content(atom, "https://example.com/atom.xml").unwrap();
+        assert_eq!(feed.articles[0].url, "https://example.com/e");
+        assert_eq!(
+            feed.articles[0].enclosures[0].url,
+            "https://example.com/e.ogg"
+        );
+        assert_eq!(feed.articles[0].enclosures[0].length, Some(99));
+    }
+
+    #[test]
+    fn test_parse_images() {
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+        <channel><title>Example</title><link>https://example.com</link>
+            <image><url>/logo.png</url><title>Example</title><link>https://example.com</link></image>
+            <item><title>Thumb</title><link>https://example.com/1</link>
+                <media:thumbnail url="https://cdn.example.com/1-thumb.jpg"/>
+                <description>&lt;img src="https://cdn.example.com/1-big.jpg"&gt;</description></item>
+            <item><title>Group</title><link>https://example.com/2</link>
+                <media:group><media:content url="https://cdn.example.com/2.mp4" type="video/mp4"/>
+                <media:thumbnail url="https://cdn.example.com/2.jpg"/></media:group></item>
+            <item><title>Inline</title><link>https://example.com/3</link>
+                <description>&lt;img src="/pixel.gif" width="1" height="1"&gt;&lt;p&gt;Hi&lt;/p&gt;&lt;img src="/3.png"&gt;</description></item>
+            <item><title>Plain</title><link>https://example.com/4</link><description>No pictures</description></item>
+        </channel></rss>"#;
+
+        let feed = parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+        assert_eq!(
+            feed.icon_url.as_deref(),
+            Some("https://example.com/logo.png")
+        );
+
+        let thumbnails: Vec<_> = feed
+            .articles
+            .iter()
+            .map(|a| a.thumbnail_url.as_deref())
+            .collect();
+        assert_eq!(
+            thumbnails,
+            vec![
+                Some("https://cdn.example.com/1-thumb.jpg"),
+                Some("https://cdn.example.com/2.jpg"),
+                Some("https://example.com/3.png"),
+                None,
+            ]
+        );
+
+        let atom = br#"<feed xmlns="http://www.w3.org/2005/Atom"><title>T</title><id>urn:t</id>
+            <icon>/favicon.png</icon><logo>/banner.png</logo></feed>"#;
+        let feed = parse_feed_content(atom, "https://example.com/atom.xml").unwrap();
+        assert_eq!(
+            feed.icon_url.as_deref(),
+            Some("https://example.com/favicon.png")
+        );
+    }
+
+    #[test]
+    fn test_older_entries_link() {
+        let atom = br#"<feed xmlns="http://www.w3.org/2005/Atom"><title>T</title><id>urn:t</id>
+            <link rel="self" href="https://example.com/feed"/>
+            <link rel="prev-archive" href="/archive/2023"/></feed>"#;
+        let feed = parse_feed_content(atom, "https://example.com/feed").unwrap();
+        assert_eq!(
+            feed.next_page_url.as_deref(),
+            Some("https://example.com/archive/2023")
+        );
+
+        // RSS carries the relations as atom:link
+        let rss = br#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+            <title>T</title><link>https://example.com</link>
+            <atom:link rel="next" href="https://example.com/feed?page=2"/></channel></rss>"#;
+        let feed = parse_feed_content(rss, "https://example.com/feed").unwrap();
+        assert_eq!(
+            feed.next_page_url.as_deref(),
+            Some("https://example.com/feed?page=2")
+        );
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let rss = br#"<rss version="2.0"><channel><title>T</title></channel></rss>"#;
+        let rdf = br#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+            xmlns="http://purl.org/rss/1.0/"><channel><title>T</title></channel></rdf:RDF>"#;
+        let atom = br#"<feed xmlns="http://www.w3.org/2005/Atom"><title>T</title></feed>"#;
+
+        for (content, format) in [
+            (&rss[..], FeedFormat::Rss2),
+            (&rdf[..], FeedFormat::Rss1),
+            (&atom[..], FeedFormat::Atom),
+        ] {
+            let feed = parse_feed_content(content, "https://example.com/feed").unwrap();
+            assert_eq!(feed.format, Some(format));
+        }
+    }
+
+    #[test]
+    fn test_parse_content_is_sanitized() {
+        let atom = br#"<?xml version="1.0" encoding="utf-8"?>
+        <feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Example</title><id>urn:example</id><updated>2024-01-01T00:00:00Z</updated>
+            <entry>
+                <title>Based</title><id>urn:1</id><updated>2024-01-01T00:00:00Z</updated>
+                <link href="https://example.com/1"/>
+                <content type="html" xml:base="https://cdn.example.com/posts/">&lt;img src="a.png" onerror="x()"&gt;&lt;script&gt;x()&lt;/script&gt;</content>
+            </entry>
+        </feed>"#;
+
+        let feed = parse_feed_content(atom, "https://example.com/atom.xml").unwrap();
+        assert_eq!(
+            feed.articles[0].content_html.as_deref(),
+            Some(r#"<img src="https://cdn.example.com/posts/a.png">"#)
+        );
+    }
 
     #[test]
     fn test_clean_html() {
@@ -105,4 +1006,43 @@ mod tests {
         assert_eq!(clean_html("Hello &amp; World"), "Hello & World");
         assert_eq!(clean_html("  Multiple   spaces  "), "Multiple spaces");
     }
+
+    #[test]
+    fn test_cache_validators() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "\"abc123\"".parse().unwrap());
+        headers.insert(
+            LAST_MODIFIED,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+
+        let validators = cache_validators(&headers);
+        assert_eq!(validators.etag.as_deref(), Some("\"abc123\""));
+        assert_eq!(
+            validators.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+
+        let validators = cache_validators(&HeaderMap::new());
+        assert!(validators.etag.is_none());
+        assert!(validators.last_modified.is_none());
+    }
+
+    #[test]
+    fn test_cache_max_age() {
+        let now = 1_445_412_480; // Wed, 21 Oct 2015 07:28:00 GMT
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CACHE_CONTROL, "public, max-age=1800".parse().unwrap());
+        headers.insert(EXPIRES, "Wed, 21 Oct 2015 09:28:00 GMT".parse().unwrap());
+        assert_eq!(cache_max_age(&headers, now), Some(1800));
+
+        headers.remove(CACHE_CONTROL);
+        assert_eq!(cache_max_age(&headers, now), Some(7200));
+
+        headers.insert(CACHE_CONTROL, "no-cache".parse().unwrap());
+        assert_eq!(cache_max_age(&headers, now), Some(0));
+
+        assert_eq!(cache_max_age(&HeaderMap::new(), now), None);
+    }
 }
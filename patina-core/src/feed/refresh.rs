@@ -0,0 +1,539 @@
+use crate::PatinaError;
+use crate::feed::http::create_refresh_client;
+use crate::feed::parser::{self, FeedResponse, FetchResult};
+use crate::feed::schedule::{backoff_delay, next_fetch_at};
+use crate::storage::db::Database;
+use crate::storage::models::{Feed, FeedValidators, RefreshOptions, RefreshReport, RefreshStatus};
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// A feed queued for fetching
+#[derive(Debug)]
+pub struct FetchJob {
+    pub feed_id: i64,
+    pub url: String,
+    pub validators: FeedValidators,
+}
+
+/// Result of fetching a single feed
+#[derive(Debug)]
+pub struct FetchOutcome {
+    pub feed_id: i64,
+    pub url: String,
+    pub response: FeedResponse,
+    pub elapsed: Duration,
+}
+
+/// Fetch feeds concurrently, handing each outcome to `on_outcome` as it
+/// completes, within the limits of [`run_limited`].
+pub fn fetch_concurrently<F>(
+    jobs: Vec<FetchJob>,
+    options: &RefreshOptions,
+    on_outcome: F,
+) -> Result<(), PatinaError>
+where
+    F: FnMut(FetchOutcome),
+{
+    let client = create_refresh_client()?;
+    let jobs = jobs.into_iter().map(|job| (job.url.clone(), job)).collect();
+
+    run_limited(
+        jobs,
+        options,
+        move |job: FetchJob| {
+            let client = client.clone();
+            async move {
+                let started = Instant::now();
+                let response = parser::fetch_feed(&client, &job.url, &job.validators).await;
+
+                FetchOutcome {
+                    feed_id: job.feed_id,
+                    url: job.url,
+                    response,
+                    elapsed: started.elapsed(),
+                }
+            }
+        },
+        on_outcome,
+    )
+}
+
+/// Run `fetch` for every job, each keyed by the URL it fetches, handing the
+/// results to `on_result` as they complete.
+///
+/// Jobs run on a single-threaded background tokio runtime, bounded by a
+/// global limit and a per-host limit; waiting on the network needs no more
+/// threads than that. `on_result` is always called on the calling thread, so
+/// database writes made from it stay serialized.
+pub fn run_limited<T, O, F, Fut, R>(
+    jobs: Vec<(String, T)>,
+    options: &RefreshOptions,
+    fetch: F,
+    mut on_result: R,
+) -> Result<(), PatinaError>
+where
+    T: Send + 'static,
+    O: Send + 'static,
+    F: Fn(T) -> Fut + Send,
+    Fut: Future<Output = O> + Send + 'static,
+    R: FnMut(O),
+{
+    if jobs.is_empty() {
+        return Ok(());
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let global = Arc::new(Semaphore::new(
+        options.max_concurrent_fetches.max(1) as usize
+    ));
+    let per_host = options.max_fetches_per_host.max(1) as usize;
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            runtime.block_on(async move {
+                let mut hosts: HashMap<String, Arc<Semaphore>> = HashMap::new();
+                let mut tasks = JoinSet::new();
+
+                for (url, job) in jobs {
+                    let host = Arc::clone(
+                        hosts
+                            .entry(host_key(&url))
+                            .or_insert_with(|| Arc::new(Semaphore::new(per_host))),
+                    );
+                    let global = Arc::clone(&global);
+                    let fetched = fetch(job);
+                    let tx = tx.clone();
+
+                    tasks.spawn(async move {
+                        // Wait for the host first so a slow host doesn't hold global slots
+                        let _host_permit = host.acquire_owned().await;
+                        let _permit = global.acquire_owned().await;
+
+                        let _ = tx.send(fetched.await);
+                    });
+                }
+
+                // Close the channel once every task has sent its result
+                drop(tx);
+                while tasks.join_next().await.is_some() {}
+            });
+        });
+
+        for result in rx {
+            on_result(result);
+        }
+    });
+
+    Ok(())
+}
+
+/// Store a fetch outcome and summarise it in a report.
+/// The error is returned as well so single-feed refreshes can surface it as-is.
+pub fn store_outcome(
+    db: &Database,
+    outcome: FetchOutcome,
+) -> (RefreshReport, Result<(), PatinaError>) {
+    let mut report = RefreshReport {
+        feed_id: outcome.feed_id,
+        status: RefreshStatus::Failed,
+        new_articles: 0,
+        new_article_ids: Vec::new(),
+        updated_articles: 0,
+        http_status: outcome.response.http_status,
+        elapsed_ms: outcome.elapsed.as_millis() as u64,
+        error: None,
+        moved_to: None,
+    };
+
+    let max_age_secs = outcome.response.max_age_secs;
+    let moved_to = outcome.response.moved_to;
+    let result = outcome
+        .response
+        .result
+        .and_then(|fetched| store_fetch_result(db, &mut report, fetched));
+
+    match &result {
+        // Retired when the feed turned out to be gone
+        Ok(()) if report.status == RefreshStatus::Retired => {}
+        Ok(()) => {
+            // Schedule the next fetch from the hints saved with the feed
+            let recorded = db
+                .get_poll_hints(report.feed_id)
+                .and_then(|hints| {
+                    let now = chrono::Utc::now().timestamp();
+                    let next = next_fetch_at(&hints, max_age_secs, now);
+                    db.record_fetch_success(report.feed_id, report.http_status, next)
+                })
+                .and_then(|()| match &moved_to {
+                    Some(url) => move_feed(db, &mut report, url),
+                    None => Ok(()),
+                });
+            if let Err(e) = recorded {
+                report.error = Some(e.to_string());
+            }
+        }
+        Err(e) => {
+            report.status = RefreshStatus::Failed;
+            report.error = Some(e.to_string());
+
+            // Back off exponentially so dead feeds aren't hit on every refresh
+            let backoff = db
+                .record_fetch_failure(report.feed_id, &e.to_string(), report.http_status)
+                .and_then(|failures| {
+                    let retry_at = chrono::Utc::now().timestamp() + backoff_delay(failures);
+                    db.schedule_next_fetch(report.feed_id, Some(retry_at))
+                });
+            if let Err(e) = backoff {
+                report.error = Some(e.to_string());
+            }
+        }
+    }
+
+    (report, result)
+}
+
+/// Follow a permanent redirect: move the subscription to the new URL,
+/// or retire it if that URL is already subscribed separately
+fn move_feed(db: &Database, report: &mut RefreshReport, url: &str) -> Result<(), PatinaError> {
+    match db.get_feed_by_url(url)? {
+        Some(existing) if existing.id != report.feed_id => {
+            let reason = format!(
+                "Moved to {}, already subscribed as \"{}\"",
+                url, existing.title
+            );
+            db.retire_feed(report.feed_id, &reason)?;
+            report.status = RefreshStatus::Retired;
+        }
+        _ => db.update_feed_url(report.feed_id, url)?,
+    }
+
+    report.moved_to = Some(url.to_string());
+    Ok(())
+}
+
+/// Report for a feed left alone because it is retired or backing off
+pub fn skipped_report(feed: &Feed) -> RefreshReport {
+    RefreshReport {
+        feed_id: feed.id,
+        status: RefreshStatus::Skipped,
+        new_articles: 0,
+        new_article_ids: Vec::new(),
+        updated_articles: 0,
+        http_status: feed.last_http_status.map(|s| s as u16),
+        elapsed_ms: 0,
+        error: feed
+            .retired_reason
+            .clone()
+            .or_else(|| feed.last_error.clone()),
+        moved_to: None,
+    }
+}
+
+/// Whether a full refresh should leave the feed alone:
+/// it is retired, or still inside its backoff window after failing
+pub fn should_skip(feed: &Feed, now: i64) -> bool {
+    feed.retired_at.is_some()
+        || (feed.consecutive_failures > 0 && feed.next_fetch_at.is_some_and(|at| at > now))
+}
+
+/// Whether a feed's scheduled fetch time has come
+pub fn is_due(feed: &Feed, now: i64) -> bool {
+    feed.retired_at.is_none() && feed.next_fetch_at.is_none_or(|at| at <= now)
+}
+
+/// Store a fetched feed: insert new articles, then update its metadata
+fn store_fetch_result(
+    db: &Database,
+    report: &mut RefreshReport,
+    fetched: FetchResult,
+) -> Result<(), PatinaError> {
+    match fetched {
+        // Nothing changed since the last fetch, leave articles alone
+        FetchResult::NotModified => report.status = RefreshStatus::NotModified,
+        FetchResult::Modified(feed_data) => {
+            let mark_unread = db.get_feed_settings(report.feed_id)?.mark_updated_unread;
+
+            // Articles already stored are checked for edits instead
+            for article in &feed_data.articles {
+                if let Some(inserted) = db.insert_article(report.feed_id, article)? {
+                    report.new_articles += 1;
+                    report.new_article_ids.push(inserted.id);
+                } else if db.update_article(report.feed_id, article, mark_unread)? {
+                    report.updated_articles += 1;
+                }
+            }
+
+            // The new cache validators are stored last: if an article failed to
+            // store, the next fetch must download the feed again, not get a 304
+            db.update_feed_metadata(report.feed_id, &feed_data)?;
+            report.status = RefreshStatus::Updated;
+        }
+        FetchResult::Gone => {
+            db.retire_feed(report.feed_id, "Feed is gone (HTTP 410)")?;
+            report.status = RefreshStatus::Retired;
+        }
+    }
+
+    Ok(())
+}
+
+/// Key used to group fetches for the per-host limit
+fn host_key(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_db::database_with_feed;
+    use crate::feed::test_server::{response, serve};
+    use crate::storage::models::ParsedFeed;
+
+    fn outcome(feed_id: i64, result: Result<FetchResult, PatinaError>) -> FetchOutcome {
+        FetchOutcome {
+            feed_id,
+            url: "https://example.com/feed.xml".to_string(),
+            response: FeedResponse {
+                http_status: Some(200),
+                max_age_secs: None,
+                moved_to: None,
+                result,
+            },
+            elapsed: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn test_store_outcome() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0"><channel><title>Example</title><link>https://example.com</link>
+            <item><title>One</title><link>https://example.com/1</link></item>
+            <item><title>Two</title><link>https://example.com/2</link></item>
+        </channel></rss>"#;
+        let parsed = || parser::parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+
+        let (report, result) = store_outcome(
+            &db,
+            outcome(feed_id, Ok(FetchResult::Modified(Box::new(parsed())))),
+        );
+        assert!(result.is_ok());
+        assert_eq!(report.status, RefreshStatus::Updated);
+        assert_eq!(report.new_articles, 2);
+        assert_eq!(report.new_article_ids.len(), 2);
+
+        // Second fetch of the same entries adds nothing
+        let (report, _) = store_outcome(
+            &db,
+            outcome(feed_id, Ok(FetchResult::Modified(Box::new(parsed())))),
+        );
+        assert_eq!(report.new_articles, 0);
+
+        let (report, _) = store_outcome(&db, outcome(feed_id, Ok(FetchResult::NotModified)));
+        assert_eq!(report.status, RefreshStatus::NotModified);
+
+        let (report, result) = store_outcome(
+            &db,
+            outcome(
+                feed_id,
+                Err(PatinaError::NetworkError("HTTP 500".to_string())),
+            ),
+        );
+        assert!(result.is_err());
+        assert_eq!(report.status, RefreshStatus::Failed);
+        assert_eq!(report.error.as_deref(), Some("Network error: HTTP 500"));
+
+        // The failure is remembered and the feed backs off
+        let feed = db.get_feed(feed_id).unwrap().unwrap();
+        assert_eq!(feed.consecutive_failures, 1);
+        assert_eq!(feed.last_error.as_deref(), Some("Network error: HTTP 500"));
+        assert!(should_skip(&feed, chrono::Utc::now().timestamp()));
+        assert_eq!(db.get_unhealthy_feeds(1).unwrap().len(), 1);
+
+        // A success clears it again
+        let (_, result) = store_outcome(&db, outcome(feed_id, Ok(FetchResult::NotModified)));
+        assert!(result.is_ok());
+        let feed = db.get_feed(feed_id).unwrap().unwrap();
+        assert_eq!(feed.consecutive_failures, 0);
+        assert!(feed.last_error.is_none());
+        assert!(!should_skip(&feed, chrono::Utc::now().timestamp()));
+
+        // ...and schedules the next fetch an hour out, as the feed gives no hints
+        let now = chrono::Utc::now().timestamp();
+        assert!(!is_due(&feed, now));
+        assert!(is_due(&feed, now + 3600));
+        assert!(db.get_unhealthy_feeds(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_store_outcome_updates_linkless_entry() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        let rss = |description: &str| {
+            format!(
+                r#"<?xml version="1.0"?>
+                <rss version="2.0"><channel><title>Example</title><link>https://example.com</link>
+                    <item><title>Status</title><description>{description}</description></item>
+                </channel></rss>"#
+            )
+        };
+        let parsed = |description: &str| {
+            parser::parse_feed_content(rss(description).as_bytes(), "https://example.com/feed.xml")
+                .unwrap()
+        };
+        let fetched = |description: &str| {
+            outcome(
+                feed_id,
+                Ok(FetchResult::Modified(Box::new(parsed(description)))),
+            )
+        };
+
+        let (report, _) = store_outcome(&db, fetched("All systems go"));
+        assert_eq!(report.new_articles, 1);
+
+        // An entry without a link or guid is recognised by its title and
+        // date, so an edit to its text is applied in place
+        let (report, _) = store_outcome(&db, fetched("Back up after an outage"));
+        assert_eq!((report.new_articles, report.updated_articles), (0, 1));
+
+        let articles = db.get_articles_for_feed(feed_id).unwrap();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(
+            articles[0].summary.as_deref(),
+            Some("Back up after an outage")
+        );
+    }
+
+    #[test]
+    fn test_store_outcome_keeps_validators_on_failure() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        db.run_migrations().unwrap();
+
+        let rss = br#"<?xml version="1.0"?>
+        <rss version="2.0"><channel><title>Example</title><link>https://example.com</link>
+            <item><title>One</title><link>https://example.com/1</link></item>
+            <item><title>Two</title><link>https://example.com/2</link></item>
+        </channel></rss>"#;
+        let mut parsed = parser::parse_feed_content(rss, "https://example.com/feed.xml").unwrap();
+        let feed_id = db.insert_feed(&parsed).unwrap().id;
+
+        // The second article can't be stored
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER fail_insert BEFORE INSERT ON articles
+                 WHEN NEW.url = 'https://example.com/2' BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+            )
+            .unwrap();
+
+        parsed.etag = Some("\"v2\"".to_string());
+        let (report, result) = store_outcome(
+            &db,
+            outcome(feed_id, Ok(FetchResult::Modified(Box::new(parsed)))),
+        );
+        assert!(result.is_err());
+        assert_eq!(report.status, RefreshStatus::Failed);
+
+        // ...so the next fetch isn't answered with a 304 that would skip it
+        assert!(db.get_feed_validators(feed_id).unwrap().etag.is_none());
+    }
+
+    #[test]
+    fn test_store_outcome_moved_and_gone() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+        let other_id = db
+            .insert_feed(&ParsedFeed {
+                title: "Other".to_string(),
+                url: "https://example.org/feed.xml".to_string(),
+                ..Default::default()
+            })
+            .unwrap()
+            .id;
+
+        // A permanent redirect moves the subscription
+        let mut moved = outcome(feed_id, Ok(FetchResult::NotModified));
+        moved.response.moved_to = Some("https://example.net/feed.xml".to_string());
+        let (report, result) = store_outcome(&db, moved);
+        assert!(result.is_ok());
+        assert_eq!(
+            report.moved_to.as_deref(),
+            Some("https://example.net/feed.xml")
+        );
+        let stored = db.get_feed(feed_id).unwrap().unwrap();
+        assert_eq!(stored.url, "https://example.net/feed.xml");
+
+        // Moving onto a URL that is already subscribed retires the old one
+        let mut moved = outcome(feed_id, Ok(FetchResult::NotModified));
+        moved.response.moved_to = Some("https://example.org/feed.xml".to_string());
+        let (report, _) = store_outcome(&db, moved);
+        assert_eq!(report.status, RefreshStatus::Retired);
+        let stored = db.get_feed(feed_id).unwrap().unwrap();
+        assert_eq!(stored.url, "https://example.net/feed.xml");
+        assert!(stored.retired_at.is_some());
+        assert!(should_skip(&stored, chrono::Utc::now().timestamp()));
+        assert!(!is_due(&stored, i64::MAX));
+
+        // 410 Gone retires the feed with a reason
+        let mut gone = outcome(other_id, Ok(FetchResult::Gone));
+        gone.response.http_status = Some(410);
+        let (report, result) = store_outcome(&db, gone);
+        assert!(result.is_ok());
+        assert_eq!(report.status, RefreshStatus::Retired);
+        let stored = db.get_feed(other_id).unwrap().unwrap();
+        assert!(stored.retired_at.is_some());
+        assert_eq!(
+            stored.retired_reason.as_deref(),
+            Some("Feed is gone (HTTP 410)")
+        );
+
+        let skipped = skipped_report(&stored);
+        assert_eq!(skipped.status, RefreshStatus::Skipped);
+        assert_eq!(skipped.error.as_deref(), Some("Feed is gone (HTTP 410)"));
+    }
+
+    #[test]
+    fn test_host_key() {
+        assert_eq!(host_key("https://Example.com/feed.xml"), "example.com");
+        assert_eq!(host_key("https://blog.example.com/rss"), "blog.example.com");
+        assert_eq!(host_key("not a url"), "");
+    }
+
+    #[test]
+    fn test_fetch_concurrently() {
+        let base = serve(|_| response("304 Not Modified", &[], ""));
+
+        let jobs = (0..5)
+            .map(|i| FetchJob {
+                feed_id: i,
+                url: format!("{}/feed{}.xml", base, i),
+                validators: FeedValidators::default(),
+            })
+            .collect();
+
+        let mut outcomes = Vec::new();
+        fetch_concurrently(jobs, &RefreshOptions::default(), |outcome| {
+            outcomes.push(outcome)
+        })
+        .unwrap();
+
+        assert_eq!(outcomes.len(), 5);
+        assert!(
+            outcomes
+                .iter()
+                .all(|o| matches!(o.response.result, Ok(FetchResult::NotModified)))
+        );
+    }
+}
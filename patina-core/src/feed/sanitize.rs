@@ -0,0 +1,207 @@
+use ammonia::{Builder, UrlRelative};
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+/// Tags kept in stored article content; anything else is unwrapped to its children
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "audio",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "iframe",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "picture",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+    "video",
+];
+
+/// Attributes allowed on any tag
+const GENERIC_ATTRIBUTES: &[&str] = &["dir", "lang", "title"];
+
+/// Attributes allowed per tag
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("abbr", &["title"]),
+    ("audio", &["controls", "src"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("iframe", &["allowfullscreen", "height", "src", "width"]),
+    ("img", &["alt", "height", "src", "width"]),
+    ("ol", &["reversed", "start", "type"]),
+    ("source", &["media", "src", "type"]),
+    ("td", &["colspan", "rowspan"]),
+    ("th", &["colspan", "rowspan", "scope"]),
+    ("time", &["datetime"]),
+    ("video", &["controls", "height", "poster", "src", "width"]),
+];
+
+/// URL schemes allowed in links and media; `javascript:` and `data:` are dropped
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Hosts whose iframes are kept as embeds; every other iframe loses its `src`
+const EMBED_HOSTS: &[&str] = &[
+    "www.youtube.com",
+    "youtube.com",
+    "www.youtube-nocookie.com",
+    "player.vimeo.com",
+    "w.soundcloud.com",
+    "open.spotify.com",
+    "bandcamp.com",
+];
+
+/// Sandbox applied to the embeds that survive
+const EMBED_SANDBOX: &str = "allow-scripts allow-same-origin allow-presentation allow-popups";
+
+/// Make feed-supplied HTML safe to render in the reader's WebView.
+///
+/// Only allowlisted tags and attributes survive, scripts and styles are removed
+/// with their contents, and links may only use http(s) or mailto. Relative
+/// `src`/`href` values are resolved against `base_url` (the entry's `xml:base`
+/// or link); without a base they are dropped.
+pub fn sanitize_html(html: &str, base_url: Option<&str>) -> String {
+    let base = base_url.and_then(|url| Url::parse(url).ok());
+    let embed_base = base.clone();
+
+    let tag_attributes: HashMap<&str, HashSet<&str>> = TAG_ATTRIBUTES
+        .iter()
+        .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect()))
+        .collect();
+
+    let url_relative = match base {
+        Some(base) => UrlRelative::RewriteWithBase(base),
+        None => UrlRelative::Deny,
+    };
+
+    Builder::default()
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .generic_attributes(GENERIC_ATTRIBUTES.iter().copied().collect())
+        .tag_attributes(tag_attributes)
+        .set_tag_attribute_value("iframe", "sandbox", EMBED_SANDBOX)
+        .url_schemes(URL_SCHEMES.iter().copied().collect())
+        .url_relative(url_relative)
+        .attribute_filter(
+            move |element, attribute, value| match (element, attribute) {
+                ("iframe", "src") if !is_allowed_embed(value, embed_base.as_ref()) => None,
+                _ => Some(value.into()),
+            },
+        )
+        .clean(html)
+        .to_string()
+}
+
+/// Whether an iframe `src` points at a known embed provider
+fn is_allowed_embed(src: &str, base: Option<&Url>) -> bool {
+    let url = match base {
+        Some(base) => base.join(src),
+        None => Url::parse(src),
+    };
+
+    url.is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| EMBED_HOSTS.contains(&host))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Option<&str> = Some("https://example.com/posts/1");
+
+    #[test]
+    fn test_strips_scripts_and_handlers() {
+        let html = r#"<p onclick="steal()">Hi<script>alert(1)</script></p><style>p{}</style>"#;
+        assert_eq!(sanitize_html(html, BASE), "<p>Hi</p>");
+
+        let html = r#"<a href="javascript:alert(1)">x</a><img src="data:image/png;base64,AA">"#;
+        assert_eq!(
+            sanitize_html(html, BASE),
+            r#"<a rel="noopener noreferrer">x</a><img>"#
+        );
+
+        // Unknown tags are unwrapped, keeping their text
+        let html = "<form><button>Go</button></form>";
+        assert_eq!(sanitize_html(html, BASE), "Go");
+    }
+
+    #[test]
+    fn test_resolves_relative_urls() {
+        let html = r#"<a href="../about">About</a><img src="/img/a.png" alt="A">"#;
+        assert_eq!(
+            sanitize_html(html, BASE),
+            r#"<a href="https://example.com/about" rel="noopener noreferrer">About</a><img src="https://example.com/img/a.png" alt="A">"#
+        );
+
+        // Without a base relative URLs can't be trusted to mean anything
+        assert_eq!(sanitize_html(r#"<img src="/img/a.png">"#, None), "<img>");
+    }
+
+    #[test]
+    fn test_iframes() {
+        let html = r#"<p>Watch</p><iframe src="https://www.youtube.com/embed/abc" width="560" onload="x()"></iframe><iframe src="https://ads.example.net/frame"></iframe>"#;
+        let clean = sanitize_html(html, BASE);
+
+        assert!(clean.contains(r#"src="https://www.youtube.com/embed/abc""#));
+        assert!(clean.contains(r#"sandbox=""#));
+        assert!(!clean.contains("onload"));
+        assert!(!clean.contains("ads.example.net"));
+        assert!(clean.ends_with(r#"<iframe sandbox="allow-scripts allow-same-origin allow-presentation allow-popups"></iframe>"#));
+
+        // Protocol-relative embeds are resolved against the base
+        let html = r#"<iframe src="//player.vimeo.com/video/1"></iframe>"#;
+        assert!(sanitize_html(html, BASE).contains("https://player.vimeo.com/video/1"));
+    }
+}
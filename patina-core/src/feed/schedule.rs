@@ -0,0 +1,133 @@
+use crate::storage::models::PollHints;
+use chrono::{DateTime, Datelike, Timelike};
+
+/// Feeds that failed this many times in a row are reported as unhealthy
+pub const UNHEALTHY_AFTER_FAILURES: i32 = 3;
+
+/// Delay before retrying after the first failure (30 minutes)
+const BACKOFF_BASE_SECS: i64 = 30 * 60;
+
+/// Longest delay between retries of a failing feed (24 hours)
+const BACKOFF_MAX_SECS: i64 = 24 * 60 * 60;
+
+/// Polling interval when a feed gives no hints (1 hour)
+const DEFAULT_INTERVAL_SECS: i64 = 60 * 60;
+
+/// Never poll more often than this, whatever the hints say (15 minutes)
+const MIN_INTERVAL_SECS: i64 = 15 * 60;
+
+/// Always poll at least once a day
+const MAX_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// Compute when a feed fetched at `fetched_at` is next due.
+///
+/// The longest of the declared intervals wins (feed `<ttl>`, `sy:updatePeriod`
+/// and the HTTP freshness lifetime), clamped to a sane range, then pushed past
+/// any `<skipHours>`/`<skipDays>`.
+pub fn next_fetch_at(hints: &PollHints, max_age_secs: Option<i64>, fetched_at: i64) -> i64 {
+    let interval = [
+        hints.ttl_minutes.map(|minutes| minutes as i64 * 60),
+        hints.update_period_secs,
+        max_age_secs,
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap_or(DEFAULT_INTERVAL_SECS)
+    .clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS);
+
+    let mut next = fetched_at + interval;
+
+    // Step an hour at a time out of skipped hours/days, at most a week ahead
+    for _ in 0..(7 * 24) {
+        let Some(time) = DateTime::from_timestamp(next, 0) else {
+            break;
+        };
+        let skipped =
+            hints.skip_hours.contains(&time.hour()) || hints.skip_days.contains(&time.weekday());
+        if !skipped {
+            break;
+        }
+        // Move to the start of the next hour
+        next += 3600 - (next.rem_euclid(3600));
+    }
+
+    next
+}
+
+/// Exponential backoff delay in seconds after `failures` consecutive failures
+pub fn backoff_delay(failures: i32) -> i64 {
+    if failures <= 0 {
+        return 0;
+    }
+
+    // Doubles with each failure, capped well before the shift could overflow
+    let exponent = (failures - 1).min(16) as u32;
+    (BACKOFF_BASE_SECS << exponent).min(BACKOFF_MAX_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Monday 2024-01-01 00:00:00 UTC
+    const MONDAY: i64 = 1_704_067_200;
+
+    #[test]
+    fn test_next_fetch_at_intervals() {
+        let hints = PollHints::default();
+        assert_eq!(
+            next_fetch_at(&hints, None, MONDAY),
+            MONDAY + DEFAULT_INTERVAL_SECS
+        );
+
+        // Longest declared interval wins
+        let hints = PollHints {
+            ttl_minutes: Some(120),
+            update_period_secs: Some(3600),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_at(&hints, Some(600), MONDAY), MONDAY + 7200);
+
+        // Clamped to the allowed range
+        assert_eq!(
+            next_fetch_at(&PollHints::default(), Some(0), MONDAY),
+            MONDAY + MIN_INTERVAL_SECS
+        );
+        let hints = PollHints {
+            update_period_secs: Some(7 * 24 * 60 * 60),
+            ..Default::default()
+        };
+        assert_eq!(
+            next_fetch_at(&hints, None, MONDAY),
+            MONDAY + MAX_INTERVAL_SECS
+        );
+    }
+
+    #[test]
+    fn test_next_fetch_at_skips() {
+        // Due at 01:00, but hours 1 and 2 are skipped
+        let hints = PollHints {
+            skip_hours: vec![1, 2],
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_at(&hints, None, MONDAY), MONDAY + 3 * 3600);
+
+        // Due on Monday 01:00, but Mondays are skipped
+        let hints = PollHints {
+            skip_days: vec![chrono::Weekday::Mon],
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_at(&hints, None, MONDAY), MONDAY + 24 * 3600);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), 0);
+        assert_eq!(backoff_delay(1), 30 * 60);
+        assert_eq!(backoff_delay(2), 60 * 60);
+        assert_eq!(backoff_delay(3), 2 * 60 * 60);
+        assert_eq!(backoff_delay(7), BACKOFF_MAX_SECS);
+        assert_eq!(backoff_delay(1000), BACKOFF_MAX_SECS);
+    }
+}
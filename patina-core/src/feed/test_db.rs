@@ -0,0 +1,19 @@
+use crate::storage::db::Database;
+use crate::storage::models::ParsedFeed;
+
+/// A migrated in-memory database subscribed to the feed at `feed_url`,
+/// returned with the feed's id
+pub fn database_with_feed(feed_url: &str) -> (Database, i64) {
+    let db = Database::new(":memory:").unwrap();
+    db.run_migrations().unwrap();
+
+    let feed = db
+        .insert_feed(&ParsedFeed {
+            title: "Example".to_string(),
+            url: feed_url.to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    (db, feed.id)
+}
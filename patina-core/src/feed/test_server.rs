@@ -0,0 +1,78 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+/// A request as the test server received it
+pub struct Request {
+    /// Path and query
+    pub path: String,
+    head: String,
+}
+
+impl Request {
+    /// Value of a request header, by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+}
+
+/// Answer every connection with the response `respond` builds, returning the base URL
+pub fn serve<F>(respond: F) -> String
+where
+    F: Fn(&Request) -> Vec<u8> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut buf = [0u8; 4096];
+            let read = stream.read(&mut buf).unwrap_or(0);
+            let head = String::from_utf8_lossy(&buf[..read]).into_owned();
+            let path = head
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+
+            let _ = stream.write_all(&respond(&Request { path, head }));
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+/// Serve bodies by path and query, answering 404 for anything else.
+/// Returns the base URL.
+pub fn serve_pages(pages: Vec<(&'static str, String)>) -> String {
+    serve(
+        move |request| match pages.iter().find(|(path, _)| *path == request.path) {
+            Some((_, body)) => response("200 OK", &[], body),
+            None => response("404 Not Found", &[], ""),
+        },
+    )
+}
+
+/// A raw HTTP response. The connection is closed after it, as the server
+/// answers one request per connection.
+pub fn response(status: &str, headers: &[(&str, &str)], body: impl AsRef<[u8]>) -> Vec<u8> {
+    let body = body.as_ref();
+    let mut head = format!(
+        "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let mut response = head.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
@@ -3,16 +3,25 @@ pub mod http;
 pub mod serendipity;
 pub mod storage;
 
-use std::sync::Arc;
+use feed::download::DownloadProgress;
+use feed::opml::ImportProgress;
+use feed::parser::{FetchResult, FetchedDocument};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use storage::db::Database;
-use storage::models::{Article, DiscoveredFeed, Feed, OpmlImportResult, ReadingPattern};
+use storage::image_cache::ImageCache;
+use storage::models::{
+    Article, ArticleContent, BackfillReport, DiscoveredFeed, Enclosure, Feed, FeedSettings, Folder,
+    ImageCacheOptions, OpmlEntryOutcome, OpmlEntryStatus, OpmlFeed, OpmlImportResult,
+    PodcastEpisode, ReaderImportResult, ReadingPattern, RefreshOptions, RefreshReport,
+    RefreshStatus,
+};
 use thiserror::Error;
 
 // Use setup_scaffolding for proc-macro based bindings
 uniffi::setup_scaffolding!();
 
 #[derive(Error, Debug, uniffi::Error)]
-#[uniffi(flat_error)]
 pub enum PatinaError {
     #[error("Database error: {0}")]
     DatabaseError(String),
@@ -28,6 +37,9 @@ pub enum PatinaError {
     IoError(String),
     #[error("Feed already exists: {0}")]
     FeedAlreadyExists(String),
+    /// The URL is a web page offering several feeds to choose from
+    #[error("Found {} feeds on the page", candidates.len())]
+    MultipleFeedsFound { candidates: Vec<DiscoveredFeed> },
 }
 
 impl From<rusqlite::Error> for PatinaError {
@@ -70,6 +82,8 @@ pub fn create_patina_core(db_path: String) -> Result<Arc<PatinaCore>, PatinaErro
 #[derive(uniffi::Object)]
 pub struct PatinaCore {
     db: Database,
+    refresh_options: Mutex<RefreshOptions>,
+    image_cache: Mutex<Option<ImageCache>>,
 }
 
 #[uniffi::export]
@@ -78,10 +92,16 @@ impl PatinaCore {
     pub fn new(db_path: String) -> Result<Self, PatinaError> {
         let db = Database::new(&db_path)?;
         db.run_migrations()?;
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            refresh_options: Mutex::new(RefreshOptions::default()),
+            image_cache: Mutex::new(None),
+        })
     }
 
     // Feed management
+    /// Subscribe to a feed, given its URL or the URL of a web page offering it.
+    /// A page offering several feeds fails with `MultipleFeedsFound`.
     pub fn add_feed(&self, url: String) -> Result<Feed, PatinaError> {
         let url = url::Url::parse(&url)?;
 
@@ -90,8 +110,48 @@ impl PatinaCore {
             return Err(PatinaError::FeedAlreadyExists(existing_feed.title));
         }
 
-        let feed_data = feed::parser::fetch_and_parse_feed(url.as_str())?;
+        let mut feed_data = match feed::parser::fetch_feed_or_page(url.as_str())? {
+            FetchedDocument::Feed(feed_data) => *feed_data,
+            FetchedDocument::Page {
+                html,
+                url: page_url,
+            } => {
+                // Well-known platforms map straight to their feeds; otherwise
+                // look for feeds in the page that was already downloaded
+                let mut probed = feed::discovery::discover_platform_feeds(&page_url)?;
+                if probed.is_empty() {
+                    probed = feed::discovery::discover_feeds_in_page(&html, &page_url)?;
+                }
+                if probed.len() > 1 {
+                    return Err(PatinaError::MultipleFeedsFound {
+                        candidates: probed.into_iter().map(|probed| probed.discovered).collect(),
+                    });
+                }
+                let Some(probed) = probed.pop() else {
+                    return Err(PatinaError::ParseError(format!("No feed found at {}", url)));
+                };
+
+                if let Some(existing_feed) = self.db.get_feed_by_url(&probed.discovered.url)? {
+                    return Err(PatinaError::FeedAlreadyExists(existing_feed.title));
+                }
+                // Probing already downloaded and parsed the feed
+                probed.feed
+            }
+        };
+
+        // Fall back to the website's favicon when the feed has no icon
+        if feed_data.icon_url.is_none() {
+            if let Some(site_url) = &feed_data.site_url {
+                feed_data.icon_url = feed::discovery::discover_favicon(site_url).ok().flatten();
+            }
+        }
+
         let feed = self.db.insert_feed(&feed_data)?;
+        self.cache_feed_icon(&feed);
+
+        let now = chrono::Utc::now().timestamp();
+        let next_fetch_at = feed::schedule::next_fetch_at(&feed_data.poll_hints, None, now);
+        self.db.schedule_next_fetch(feed.id, Some(next_fetch_at))?;
 
         // Insert articles
         for article in feed_data.articles {
@@ -112,32 +172,61 @@ impl PatinaCore {
 
     pub fn refresh_feed(&self, feed_id: i64) -> Result<Feed, PatinaError> {
         let feed = self.db.get_feed(feed_id)?.ok_or(PatinaError::NotFound)?;
-        let feed_data = feed::parser::fetch_and_parse_feed(&feed.url)?;
 
-        // Update feed metadata
-        self.db.update_feed_metadata(feed_id, &feed_data)?;
-
-        // Insert new articles (duplicates will be ignored)
-        for article in feed_data.articles {
-            let _ = self.db.insert_article(feed_id, &article);
-        }
+        let mut result = Ok(());
+        self.refresh_feeds(vec![feed], |_, r| result = r)?;
+        result?;
 
         // Return updated feed
         self.db.get_feed(feed_id)?.ok_or(PatinaError::NotFound)
     }
 
-    pub fn refresh_all_feeds(&self) -> Result<Vec<Feed>, PatinaError> {
-        let feeds = self.db.get_all_feeds()?;
-        let mut results = Vec::new();
+    /// Refresh every feed, reporting the outcome of each one.
+    /// Retired feeds and feeds backing off after repeated failures are skipped.
+    pub fn refresh_all_feeds(&self) -> Result<Vec<RefreshReport>, PatinaError> {
+        let now = chrono::Utc::now().timestamp();
+        let (skipped, feeds): (Vec<Feed>, Vec<Feed>) = self
+            .db
+            .get_all_feeds()?
+            .into_iter()
+            .partition(|feed| feed::refresh::should_skip(feed, now));
+
+        let mut reports: Vec<RefreshReport> =
+            skipped.iter().map(feed::refresh::skipped_report).collect();
+        self.refresh_feeds(feeds, |report, _| reports.push(report))?;
+
+        Ok(reports)
+    }
+
+    /// Refresh only the feeds whose scheduled fetch time has come,
+    /// honouring the polling hints publishers declare.
+    pub fn refresh_due_feeds(&self) -> Result<Vec<RefreshReport>, PatinaError> {
+        let now = chrono::Utc::now().timestamp();
+        let feeds: Vec<Feed> = self
+            .db
+            .get_all_feeds()?
+            .into_iter()
+            .filter(|feed| feed::refresh::is_due(feed, now))
+            .collect();
+
+        let mut reports = Vec::with_capacity(feeds.len());
+        self.refresh_feeds(feeds, |report, _| reports.push(report))?;
+
+        Ok(reports)
+    }
 
-        for feed in feeds {
-            match self.refresh_feed(feed.id) {
-                Ok(updated_feed) => results.push(updated_feed),
-                Err(_) => results.push(feed), // Keep original on error
-            }
-        }
+    /// Feeds that keep failing to refresh
+    pub fn get_unhealthy_feeds(&self) -> Result<Vec<Feed>, PatinaError> {
+        self.db
+            .get_unhealthy_feeds(feed::schedule::UNHEALTHY_AFTER_FAILURES)
+    }
 
-        Ok(results)
+    pub fn get_refresh_options(&self) -> RefreshOptions {
+        self.refresh_options.lock().unwrap().clone()
+    }
+
+    pub fn set_refresh_options(&self, options: RefreshOptions) {
+        *self.refresh_options.lock().unwrap() = options;
     }
 
     // Feed discovery
@@ -145,15 +234,185 @@ impl PatinaCore {
         feed::discovery::discover_feeds(&website_url)
     }
 
+    /// Import up to `max_pages` pages of a feed's older entries. They are
+    /// kept unread for serendipity without counting as new.
+    pub fn backfill_feed(
+        &self,
+        feed_id: i64,
+        max_pages: u32,
+    ) -> Result<BackfillReport, PatinaError> {
+        let feed = self.db.get_feed(feed_id)?.ok_or(PatinaError::NotFound)?;
+        feed::backfill::backfill_feed(&self.db, &feed, max_pages)
+    }
+
+    /// Look for an icon again, e.g. for feeds added before icons were kept
+    pub fn discover_feed_icon(&self, feed_id: i64) -> Result<Feed, PatinaError> {
+        let feed = self.db.get_feed(feed_id)?.ok_or(PatinaError::NotFound)?;
+
+        let site_url = feed.site_url.as_deref().unwrap_or(&feed.url);
+        if let Some(icon_url) = feed::discovery::discover_favicon(site_url)? {
+            self.db.set_feed_icon(feed_id, &icon_url)?;
+        }
+
+        let feed = self.db.get_feed(feed_id)?.ok_or(PatinaError::NotFound)?;
+        self.cache_feed_icon(&feed);
+        Ok(feed)
+    }
+
+    pub fn get_feed_settings(&self, feed_id: i64) -> Result<FeedSettings, PatinaError> {
+        self.db.get_feed_settings(feed_id)
+    }
+
+    pub fn set_feed_settings(
+        &self,
+        feed_id: i64,
+        settings: FeedSettings,
+    ) -> Result<(), PatinaError> {
+        self.db.update_feed_settings(feed_id, &settings)
+    }
+
+    // Image cache
+
+    /// Keep local copies of feed icons and thumbnails, or stop with `None`.
+    /// Icons are cached as feeds are added and refreshed.
+    pub fn set_image_cache_options(&self, options: Option<ImageCacheOptions>) {
+        *self.image_cache.lock().unwrap() = options.as_ref().map(ImageCache::new);
+    }
+
+    /// Local path of a cached image, without touching the network
+    pub fn get_cached_image(&self, url: String) -> Option<String> {
+        let cache = self.image_cache.lock().unwrap().clone()?;
+        cache
+            .get(&url)
+            .map(|path| path.to_string_lossy().into_owned())
+    }
+
+    /// Local path of an image, downloading it into the cache first if needed
+    pub fn cache_image(&self, url: String) -> Result<String, PatinaError> {
+        let cache = self
+            .image_cache
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| PatinaError::IoError("Image cache is not enabled".to_string()))?;
+
+        let path = cache.fetch(&url)?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    pub fn clear_image_cache(&self) -> Result<(), PatinaError> {
+        let cache = self.image_cache.lock().unwrap().clone();
+        match cache {
+            Some(cache) => cache.clear(),
+            None => Ok(()),
+        }
+    }
+
     // Article management
     pub fn get_articles_for_feed(&self, feed_id: i64) -> Result<Vec<Article>, PatinaError> {
         self.db.get_articles_for_feed(feed_id)
     }
 
+    pub fn get_articles_by_author(&self, author: String) -> Result<Vec<Article>, PatinaError> {
+        self.db.get_articles_by_author(&author)
+    }
+
+    pub fn get_articles_by_category(&self, category: String) -> Result<Vec<Article>, PatinaError> {
+        self.db.get_articles_by_category(&category)
+    }
+
     pub fn get_all_unread_articles(&self) -> Result<Vec<Article>, PatinaError> {
         self.db.get_all_unread_articles()
     }
 
+    /// Fetch an article's web page and store its main content, for feeds
+    /// that only ship a teaser
+    pub fn fetch_full_text(&self, article_id: i64) -> Result<ArticleContent, PatinaError> {
+        let article = self
+            .db
+            .get_article(article_id)?
+            .ok_or(PatinaError::NotFound)?;
+
+        let html = feed::fulltext::fetch_full_text(&article.url)?;
+        self.db.set_article_full_text(article_id, &html)?;
+
+        self.get_article_content(article_id)
+    }
+
+    /// Get the full content of an article for the reader
+    pub fn get_article_content(&self, article_id: i64) -> Result<ArticleContent, PatinaError> {
+        self.db
+            .get_article_content(article_id)?
+            .ok_or(PatinaError::NotFound)
+    }
+
+    /// Get the media files attached to an article
+    pub fn get_article_enclosures(&self, article_id: i64) -> Result<Vec<Enclosure>, PatinaError> {
+        self.db.get_enclosures(article_id)
+    }
+
+    /// Get the podcast metadata of an article, if it is an episode
+    pub fn get_podcast_episode(
+        &self,
+        article_id: i64,
+    ) -> Result<Option<PodcastEpisode>, PatinaError> {
+        self.db.get_podcast_episode(article_id)
+    }
+
+    /// Download an enclosure into `directory`, resuming an interrupted
+    /// download where it stopped. Blocks until the file is complete.
+    pub fn download_enclosure(
+        &self,
+        enclosure_id: i64,
+        directory: String,
+        progress: Option<Arc<dyn DownloadProgress>>,
+    ) -> Result<Enclosure, PatinaError> {
+        let enclosure = self
+            .db
+            .get_enclosure(enclosure_id)?
+            .ok_or(PatinaError::NotFound)?;
+
+        let downloaded = enclosure
+            .local_path
+            .as_deref()
+            .is_some_and(|path| std::path::Path::new(path).exists());
+        if downloaded {
+            return Ok(enclosure);
+        }
+
+        let path = feed::download::download_enclosure(
+            &enclosure,
+            std::path::Path::new(&directory),
+            progress.as_deref(),
+        )?;
+        self.db
+            .set_enclosure_download(enclosure_id, Some(&path.to_string_lossy()))?;
+
+        self.db
+            .get_enclosure(enclosure_id)?
+            .ok_or(PatinaError::NotFound)
+    }
+
+    /// Delete a downloaded enclosure's file
+    pub fn delete_enclosure_download(&self, enclosure_id: i64) -> Result<Enclosure, PatinaError> {
+        let enclosure = self
+            .db
+            .get_enclosure(enclosure_id)?
+            .ok_or(PatinaError::NotFound)?;
+
+        if let Some(path) = &enclosure.local_path {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        self.db.set_enclosure_download(enclosure_id, None)?;
+
+        self.db
+            .get_enclosure(enclosure_id)?
+            .ok_or(PatinaError::NotFound)
+    }
+
     pub fn get_recent_articles(&self, limit: i32) -> Result<Vec<Article>, PatinaError> {
         self.db.get_recent_articles(limit)
     }
@@ -173,30 +432,135 @@ impl PatinaCore {
         self.db.mark_article_unread(article_id)
     }
 
+    pub fn star_article(&self, article_id: i64) -> Result<(), PatinaError> {
+        self.db.set_article_starred(article_id, true)
+    }
+
+    pub fn unstar_article(&self, article_id: i64) -> Result<(), PatinaError> {
+        self.db.set_article_starred(article_id, false)
+    }
+
+    /// Get starred articles, newest first
+    pub fn get_starred_articles(&self) -> Result<Vec<Article>, PatinaError> {
+        self.db.get_starred_articles()
+    }
+
+    // Folders
+    pub fn create_folder(
+        &self,
+        name: String,
+        parent_id: Option<i64>,
+    ) -> Result<Folder, PatinaError> {
+        self.db.create_folder(&name, parent_id)
+    }
+
+    /// Get every folder; counts include the feeds of subfolders
+    pub fn get_all_folders(&self) -> Result<Vec<Folder>, PatinaError> {
+        self.db.get_all_folders()
+    }
+
+    pub fn rename_folder(&self, folder_id: i64, name: String) -> Result<(), PatinaError> {
+        self.db.rename_folder(folder_id, &name)
+    }
+
+    /// Move a folder into another one, or to the top level with `None`
+    pub fn move_folder(&self, folder_id: i64, parent_id: Option<i64>) -> Result<(), PatinaError> {
+        self.db.move_folder(folder_id, parent_id)
+    }
+
+    /// Delete a folder and its subfolders, keeping their feeds
+    pub fn delete_folder(&self, folder_id: i64) -> Result<(), PatinaError> {
+        self.db.delete_folder(folder_id)
+    }
+
+    pub fn add_feed_to_folder(&self, feed_id: i64, folder_id: i64) -> Result<(), PatinaError> {
+        self.db.add_feed_to_folder(feed_id, folder_id)
+    }
+
+    pub fn remove_feed_from_folder(&self, feed_id: i64, folder_id: i64) -> Result<(), PatinaError> {
+        self.db.remove_feed_from_folder(feed_id, folder_id)
+    }
+
+    /// Get the feeds filed directly in a folder
+    pub fn get_feeds_in_folder(&self, folder_id: i64) -> Result<Vec<Feed>, PatinaError> {
+        self.db.get_feeds_in_folder(folder_id)
+    }
+
+    /// Get the articles of every feed in a folder and its subfolders
+    pub fn get_articles_in_folder(&self, folder_id: i64) -> Result<Vec<Article>, PatinaError> {
+        self.db.get_articles_in_folder(folder_id)
+    }
+
     // OPML import
-    pub fn import_opml(&self, opml_content: String) -> Result<OpmlImportResult, PatinaError> {
-        let feeds = feed::opml::parse_opml(&opml_content)?;
-        let total_feeds = feeds.len() as i32;
-        let mut imported_feeds = 0;
-        let mut failed_feeds = 0;
-        let mut errors = Vec::new();
 
-        for opml_feed in feeds {
-            match self.add_feed(opml_feed.url.clone()) {
-                Ok(_) => imported_feeds += 1,
-                Err(e) => {
-                    failed_feeds += 1;
-                    errors.push(format!("{}: {}", opml_feed.url, e));
-                }
+    /// Import the feeds of an OPML file, filed in its folders.
+    ///
+    /// Every feed is subscribed right away under its OPML title, then all of
+    /// them are fetched concurrently. Feeds that can't be reached stay
+    /// subscribed as pending and are fetched again on later refreshes.
+    pub fn import_opml(
+        &self,
+        opml_content: String,
+        progress: Option<Arc<dyn ImportProgress>>,
+    ) -> Result<OpmlImportResult, PatinaError> {
+        self.run_opml_import(&opml_content, false, progress)
+    }
+
+    /// Preview an OPML import: feeds are fetched, but nothing is stored
+    pub fn preview_opml_import(
+        &self,
+        opml_content: String,
+        progress: Option<Arc<dyn ImportProgress>>,
+    ) -> Result<OpmlImportResult, PatinaError> {
+        self.run_opml_import(&opml_content, true, progress)
+    }
+
+    /// Export all subscriptions as an OPML 2.0 document, nested by folder.
+    /// A feed in several folders is listed in each of them.
+    pub fn export_opml(&self) -> Result<String, PatinaError> {
+        let paths = self.folder_paths()?;
+        let mut memberships: HashMap<i64, Vec<i64>> = HashMap::new();
+        for (feed_id, folder_id) in self.db.get_folder_memberships()? {
+            memberships.entry(feed_id).or_default().push(folder_id);
+        }
+
+        let mut feeds = Vec::new();
+        for feed in self.db.get_all_feeds()? {
+            // Feeds outside any folder known here are listed at the top level
+            let mut folders: Vec<Vec<String>> = memberships
+                .get(&feed.id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| paths.get(id).cloned())
+                .collect();
+            if folders.is_empty() {
+                folders.push(Vec::new());
+            }
+
+            for folder in folders {
+                feeds.push(OpmlFeed {
+                    url: feed.url.clone(),
+                    title: Some(feed.title.clone()),
+                    site_url: feed.site_url.clone(),
+                    folder,
+                });
             }
         }
 
-        Ok(OpmlImportResult {
-            total_feeds,
-            imported_feeds,
-            failed_feeds,
-            errors,
-        })
+        let now = chrono::Utc::now().timestamp();
+        feed::opml::write_opml("Patina Subscriptions", &feeds, now)
+    }
+
+    // Other readers
+
+    /// Import subscriptions, articles and their read and starred state from
+    /// a Google Reader (Inoreader, FreshRSS), Miniflux or Feedbin export.
+    ///
+    /// New feeds are subscribed as pending and fetched on the next refresh.
+    /// State is merged into articles already stored, never cleared.
+    pub fn import_reader_export(&self, content: String) -> Result<ReaderImportResult, PatinaError> {
+        let export = feed::importer::parse_reader_export(&content)?;
+        self.db.import_reader_export(&export)
     }
 
     // Serendipity
@@ -226,6 +590,240 @@ impl PatinaCore {
 }
 
 impl PatinaCore {
+    // Fetch feeds concurrently, storing each result as it arrives (not exported)
+    fn refresh_feeds<F>(&self, feeds: Vec<Feed>, mut on_result: F) -> Result<(), PatinaError>
+    where
+        F: FnMut(RefreshReport, Result<(), PatinaError>),
+    {
+        let jobs = feeds
+            .into_iter()
+            .map(|feed| {
+                Ok(feed::refresh::FetchJob {
+                    validators: self.db.get_feed_validators(feed.id)?,
+                    feed_id: feed.id,
+                    url: feed.url,
+                })
+            })
+            .collect::<Result<Vec<_>, PatinaError>>()?;
+
+        let mut new_articles = Vec::new();
+        let mut updated_feeds = Vec::new();
+        let options = self.get_refresh_options();
+        feed::refresh::fetch_concurrently(jobs, &options, |outcome| {
+            let (report, result) = feed::refresh::store_outcome(&self.db, outcome);
+            if !report.new_article_ids.is_empty() {
+                new_articles.push((report.feed_id, report.new_article_ids.clone()));
+            }
+            if report.status == RefreshStatus::Updated {
+                updated_feeds.push(report.feed_id);
+            }
+            on_result(report, result);
+        })?;
+
+        // Icons may have changed or not been cached yet
+        for feed_id in updated_feeds {
+            if let Some(feed) = self.db.get_feed(feed_id)? {
+                self.cache_feed_icon(&feed);
+            }
+        }
+
+        // Full-text extraction runs once every feed is stored, within the
+        // same concurrency limits
+        let mut articles = Vec::new();
+        for (feed_id, article_ids) in new_articles {
+            if !self.db.get_feed_settings(feed_id)?.full_text_enabled {
+                continue;
+            }
+            for article_id in article_ids {
+                if let Some(article) = self.db.get_article(article_id)? {
+                    articles.push((article_id, article.url));
+                }
+            }
+        }
+        feed::fulltext::fetch_full_texts(articles, &options, |article_id, result| {
+            if let Ok(html) = result {
+                let _ = self.db.set_article_full_text(article_id, &html);
+            }
+        })
+    }
+
+    // Import or preview the feeds of an OPML file (not exported)
+    fn run_opml_import(
+        &self,
+        opml_content: &str,
+        dry_run: bool,
+        progress: Option<Arc<dyn ImportProgress>>,
+    ) -> Result<OpmlImportResult, PatinaError> {
+        let feeds = feed::opml::parse_opml(opml_content)?;
+        let total = feeds.len() as u32;
+        let (mut imported, mut pending, mut skipped, mut failed) = (0, 0, 0, 0);
+        let report = |imported: u32, pending: u32, skipped: u32, failed: u32, url: &str| {
+            if let Some(progress) = &progress {
+                progress.on_progress(imported, pending, skipped, failed, total, url.to_string());
+            }
+        };
+
+        // Settle what can be told without the network, and subscribe to the
+        // rest right away so the library is complete before any fetch
+        let mut entries = Vec::with_capacity(feeds.len());
+        let mut seen_urls = HashSet::new();
+        let mut placeholders = Vec::new();
+        let mut jobs = Vec::new();
+        let mut entry_of_job = HashMap::new();
+        for opml_feed in feeds {
+            let mut entry = OpmlEntryOutcome {
+                url: opml_feed.url.clone(),
+                title: opml_feed.title.clone(),
+                status: OpmlEntryStatus::Imported,
+                error: None,
+            };
+
+            let url = match url::Url::parse(&opml_feed.url) {
+                Ok(url) => url.to_string(),
+                Err(e) => {
+                    entry.status = OpmlEntryStatus::InvalidUrl;
+                    entry.error = Some(PatinaError::from(e).to_string());
+                    failed += 1;
+                    report(imported, pending, skipped, failed, &entry.url);
+                    entries.push(entry);
+                    continue;
+                }
+            };
+            entry.url = url.clone();
+
+            // Feeds listed again are still filed in each of their folders
+            let duplicate = !seen_urls.insert(url.clone());
+            let existing = self.db.get_feed_by_url(&url)?;
+            if duplicate || existing.is_some() {
+                entry.status = if duplicate {
+                    OpmlEntryStatus::DuplicateInFile
+                } else {
+                    OpmlEntryStatus::AlreadySubscribed
+                };
+                if let (Some(existing), false) = (existing, dry_run) {
+                    self.db.file_feed(existing.id, &opml_feed.folder)?;
+                }
+                skipped += 1;
+                report(imported, pending, skipped, failed, &url);
+                entries.push(entry);
+                continue;
+            }
+
+            if dry_run {
+                let job_id = entries.len() as i64;
+                jobs.push(feed::refresh::FetchJob {
+                    feed_id: job_id,
+                    url: url.clone(),
+                    validators: Default::default(),
+                });
+                entry_of_job.insert(job_id, entries.len());
+            } else {
+                let title = opml_feed.title.as_deref().unwrap_or(&url);
+                let feed =
+                    self.db
+                        .insert_pending_feed(&url, title, opml_feed.site_url.as_deref())?;
+                self.db.file_feed(feed.id, &opml_feed.folder)?;
+                entry_of_job.insert(feed.id, entries.len());
+                placeholders.push(feed);
+            }
+            entries.push(entry);
+        }
+
+        let mut finish = |job_id: i64, result: Result<(), PatinaError>| {
+            let entry = &mut entries[entry_of_job[&job_id]];
+            match result {
+                Ok(()) => imported += 1,
+                Err(e) => {
+                    entry.status = match e {
+                        PatinaError::ParseError(_) => OpmlEntryStatus::ParseFailed,
+                        _ => OpmlEntryStatus::FetchFailed,
+                    };
+                    entry.error = Some(e.to_string());
+                    pending += 1;
+                }
+            }
+            report(imported, pending, skipped, failed, &entry.url);
+        };
+
+        if dry_run {
+            let options = self.get_refresh_options();
+            feed::refresh::fetch_concurrently(jobs, &options, |outcome| {
+                let result = outcome.response.result.and_then(|fetched| match fetched {
+                    FetchResult::Gone => {
+                        Err(PatinaError::NetworkError("HTTP 410 Gone".to_string()))
+                    }
+                    _ => Ok(()),
+                });
+                finish(outcome.feed_id, result);
+            })?;
+        } else {
+            self.refresh_feeds(placeholders, |refresh_report, result| {
+                // A feed found gone is retired rather than failed
+                let result = result.and_then(|()| match refresh_report.status {
+                    RefreshStatus::Retired => Err(PatinaError::NetworkError(
+                        refresh_report
+                            .error
+                            .clone()
+                            .unwrap_or_else(|| "Feed is gone".to_string()),
+                    )),
+                    _ => Ok(()),
+                });
+                finish(refresh_report.feed_id, result);
+            })?;
+        }
+
+        let count = |statuses: &[OpmlEntryStatus]| {
+            entries
+                .iter()
+                .filter(|entry| statuses.contains(&entry.status))
+                .count() as i32
+        };
+
+        Ok(OpmlImportResult {
+            total_feeds: total as i32,
+            imported_feeds: count(&[OpmlEntryStatus::Imported]),
+            pending_feeds: count(&[OpmlEntryStatus::FetchFailed, OpmlEntryStatus::ParseFailed]),
+            skipped_feeds: count(&[
+                OpmlEntryStatus::AlreadySubscribed,
+                OpmlEntryStatus::DuplicateInFile,
+            ]),
+            failed_feeds: count(&[OpmlEntryStatus::InvalidUrl]),
+            entries,
+        })
+    }
+
+    /// Names leading to each folder from the top level, keyed by folder id
+    fn folder_paths(&self) -> Result<HashMap<i64, Vec<String>>, PatinaError> {
+        let folders: HashMap<i64, Folder> = self
+            .db
+            .get_all_folders()?
+            .into_iter()
+            .map(|folder| (folder.id, folder))
+            .collect();
+
+        Ok(folders
+            .keys()
+            .map(|&id| {
+                let mut path = Vec::new();
+                let mut current = folders.get(&id);
+                while let Some(folder) = current {
+                    path.push(folder.name.clone());
+                    current = folder.parent_id.and_then(|parent| folders.get(&parent));
+                }
+                path.reverse();
+                (id, path)
+            })
+            .collect())
+    }
+
+    // Download a feed's icon into the image cache, if enabled (not exported)
+    fn cache_feed_icon(&self, feed: &Feed) {
+        let cache = self.image_cache.lock().unwrap().clone();
+        if let (Some(cache), Some(icon_url)) = (cache, &feed.icon_url) {
+            let _ = cache.fetch(icon_url);
+        }
+    }
+
     // Internal serendipity helper (not exported)
     fn serendipity_record_reading(&self, article: &Article) {
         // Extract topics and record reading
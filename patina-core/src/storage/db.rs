@@ -1,10 +1,27 @@
 use crate::PatinaError;
-use crate::storage::models::{Article, Feed, ParsedArticle, ParsedFeed, ReadingPattern};
+use crate::storage::migrations;
+use crate::storage::models::{
+    Article, ArticleContent, Enclosure, Feed, FeedFormat, FeedSettings, FeedValidators, Folder,
+    ParsedArticle, ParsedFeed, PodcastEpisode, PollHints, ReaderExport, ReaderImportResult,
+    ReadingPattern,
+};
 use rusqlite::{Connection, Row, params};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Mutex;
 
+/// Columns `map_feed_row` expects, selected from `feeds f`. The unread count's
+/// correlated subquery uses the partial covering index idx_articles_feed_unread.
+const FEED_COLUMNS: &str = r#"f.id, f.title, f.url, f.site_url, f.last_fetched_at, f.created_at,
+       (SELECT COUNT(*) FROM articles a
+        WHERE a.feed_id = f.id AND a.is_read = 0 AND a.backfilled = 0) as unread_count,
+       f.consecutive_failures, f.last_error, f.last_success_at, f.last_http_status,
+       f.next_fetch_at, f.retired_at, f.retired_reason, f.format, f.icon_url, f.pending"#;
+
 /// Maps a database row to a Feed struct.
-/// Expected columns: id, title, url, site_url, last_fetched_at, created_at, unread_count
+/// Expected columns: id, title, url, site_url, last_fetched_at, created_at, unread_count,
+/// consecutive_failures, last_error, last_success_at, last_http_status, next_fetch_at,
+/// retired_at, retired_reason, format, icon_url, pending
 fn map_feed_row(row: &Row) -> Result<Feed, rusqlite::Error> {
     Ok(Feed {
         id: row.get(0)?,
@@ -14,11 +31,36 @@ fn map_feed_row(row: &Row) -> Result<Feed, rusqlite::Error> {
         last_fetched_at: row.get(4)?,
         created_at: row.get(5)?,
         unread_count: row.get(6)?,
+        consecutive_failures: row.get(7)?,
+        last_error: row.get(8)?,
+        last_success_at: row.get(9)?,
+        last_http_status: row.get(10)?,
+        next_fetch_at: row.get(11)?,
+        retired_at: row.get(12)?,
+        retired_reason: row.get(13)?,
+        format: row
+            .get::<_, Option<String>>(14)?
+            .as_deref()
+            .and_then(FeedFormat::from_name),
+        icon_url: row.get(15)?,
+        pending: row.get::<_, i32>(16)? != 0,
     })
 }
 
+/// Columns `map_article_row` expects, selected from `articles a JOIN feeds f`
+const ARTICLE_COLUMNS: &str = r#"a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
+       a.is_read, a.read_at, f.title as feed_title, a.updated_at, a.revision,
+       (SELECT json_group_array(name) FROM (
+           SELECT au.name FROM article_authors aa JOIN authors au ON au.id = aa.author_id
+           WHERE aa.article_id = a.id ORDER BY aa.position)) as authors,
+       (SELECT json_group_array(name) FROM (
+           SELECT c.name FROM article_categories ac JOIN categories c ON c.id = ac.category_id
+           WHERE ac.article_id = a.id ORDER BY ac.position)) as categories,
+       a.thumbnail_url, a.starred"#;
+
 /// Maps a database row to an Article struct.
-/// Expected columns: id, feed_id, title, url, summary, published_at, fetched_at, is_read, read_at, feed_title
+/// Expected columns: id, feed_id, title, url, summary, published_at, fetched_at, is_read, read_at, feed_title,
+/// updated_at, revision, authors, categories, thumbnail_url, starred
 fn map_article_row(row: &Row) -> Result<Article, rusqlite::Error> {
     Ok(Article {
         id: row.get(0)?,
@@ -31,9 +73,351 @@ fn map_article_row(row: &Row) -> Result<Article, rusqlite::Error> {
         is_read: row.get::<_, i32>(7)? != 0,
         read_at: row.get(8)?,
         feed_title: row.get(9)?,
+        updated_at: row.get(10)?,
+        revision: row.get(11)?,
+        authors: json_list(row.get(12)?),
+        categories: json_list(row.get(13)?),
+        thumbnail_url: row.get(14)?,
+        starred: row.get::<_, i32>(15)? != 0,
+    })
+}
+
+/// Maps a database row to an Enclosure struct.
+/// Expected columns: id, article_id, url, mime_type, length, duration_secs, local_path,
+/// downloaded_at
+fn map_enclosure_row(row: &Row) -> Result<Enclosure, rusqlite::Error> {
+    Ok(Enclosure {
+        id: row.get(0)?,
+        article_id: row.get(1)?,
+        url: row.get(2)?,
+        mime_type: row.get(3)?,
+        length: row.get(4)?,
+        duration_secs: row.get(5)?,
+        local_path: row.get(6)?,
+        downloaded_at: row.get(7)?,
     })
 }
 
+/// Maps a database row to a Folder struct.
+/// Expected columns: id, name, parent_id, created_at, unread_count, feed_count
+fn map_folder_row(row: &Row) -> Result<Folder, rusqlite::Error> {
+    Ok(Folder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        parent_id: row.get(2)?,
+        created_at: row.get(3)?,
+        unread_count: row.get(4)?,
+        feed_count: row.get(5)?,
+    })
+}
+
+/// Decode a `json_group_array` column
+fn json_list(json: Option<String>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Key an article is stored under: its guid, or its URL when it has none
+fn article_guid(article: &ParsedArticle) -> &str {
+    if article.guid.is_empty() {
+        &article.url
+    } else {
+        &article.guid
+    }
+}
+
+/// Link an article to its authors and categories, replacing any earlier links
+fn link_article_terms(
+    conn: &Connection,
+    article_id: i64,
+    article: &ParsedArticle,
+) -> Result<(), rusqlite::Error> {
+    let terms = [
+        ("authors", "article_authors", "author_id", &article.authors),
+        (
+            "categories",
+            "article_categories",
+            "category_id",
+            &article.categories,
+        ),
+    ];
+
+    for (table, link_table, key, names) in terms {
+        conn.prepare_cached(&format!("DELETE FROM {link_table} WHERE article_id = ?1"))?
+            .execute(params![article_id])?;
+
+        for (position, name) in names.iter().enumerate() {
+            conn.prepare_cached(&format!("INSERT OR IGNORE INTO {table} (name) VALUES (?1)"))?
+                .execute(params![name])?;
+            conn.prepare_cached(&format!(
+                "INSERT OR IGNORE INTO {link_table} (article_id, {key}, position)
+                 SELECT ?1, id, ?2 FROM {table} WHERE name = ?3"
+            ))?
+            .execute(params![article_id, position as i64, name])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Store an article's enclosures and podcast metadata. Enclosures are only
+/// added or refreshed, never removed, so finished downloads stay attached.
+fn store_article_media(
+    conn: &Connection,
+    article_id: i64,
+    article: &ParsedArticle,
+) -> Result<(), rusqlite::Error> {
+    for enclosure in &article.enclosures {
+        conn.prepare_cached(
+            r#"
+            INSERT INTO enclosures (article_id, url, mime_type, length, duration_secs)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(article_id, url) DO UPDATE SET
+                mime_type = excluded.mime_type,
+                length = excluded.length,
+                duration_secs = excluded.duration_secs
+            "#,
+        )?
+        .execute(params![
+            article_id,
+            enclosure.url,
+            enclosure.mime_type,
+            enclosure.length,
+            enclosure.duration_secs
+        ])?;
+    }
+
+    if let Some(podcast) = &article.podcast {
+        conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO podcast_episodes
+                (article_id, episode, season, duration_secs, explicit, image_url)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )?
+        .execute(params![
+            article_id,
+            podcast.episode,
+            podcast.season,
+            podcast.duration_secs,
+            podcast.explicit,
+            podcast.image_url
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// [`Database::insert_pending_feed`] on a locked connection or transaction
+fn insert_pending_feed(
+    conn: &Connection,
+    url: &str,
+    title: &str,
+    site_url: Option<&str>,
+) -> Result<Feed, rusqlite::Error> {
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        r#"
+        INSERT INTO feeds (title, url, site_url, created_at, pending)
+        VALUES (?1, ?2, ?3, ?4, 1)
+        "#,
+        params![title, url, site_url, now],
+    )?;
+
+    Ok(Feed {
+        id: conn.last_insert_rowid(),
+        title: title.to_string(),
+        url: url.to_string(),
+        site_url: site_url.map(str::to_string),
+        last_fetched_at: None,
+        created_at: now,
+        unread_count: 0,
+        consecutive_failures: 0,
+        last_error: None,
+        last_success_at: None,
+        last_http_status: None,
+        next_fetch_at: None,
+        retired_at: None,
+        retired_reason: None,
+        format: None,
+        icon_url: None,
+        pending: true,
+    })
+}
+
+/// [`Database::get_or_create_folder`] on a locked connection or transaction
+fn get_or_create_folder(
+    conn: &Connection,
+    name: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, rusqlite::Error> {
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT OR IGNORE INTO folders (name, parent_id, created_at) VALUES (?1, ?2, ?3)",
+        params![name, parent_id, now],
+    )?;
+
+    let id = conn.query_row(
+        "SELECT id FROM folders WHERE IFNULL(parent_id, 0) = IFNULL(?1, 0) AND name = ?2",
+        params![parent_id, name],
+        |row| row.get(0),
+    )?;
+
+    Ok(id)
+}
+
+/// Insert an article, returning `None` if the feed already has it.
+/// Backfilled articles are kept out of unread counts and lists.
+fn insert_article(
+    conn: &Connection,
+    feed_id: i64,
+    article: &ParsedArticle,
+    backfilled: bool,
+) -> Result<Option<Article>, rusqlite::Error> {
+    let now = chrono::Utc::now().timestamp();
+
+    let guid = article_guid(article);
+
+    // Articles stored before their guid was known are keyed by URL: adopt
+    // the real guid instead of storing the entry a second time
+    conn.execute(
+        r#"
+        UPDATE articles SET guid = ?1
+        WHERE feed_id = ?2 AND url = ?3 AND guid = url AND guid != ?1
+          AND NOT EXISTS (SELECT 1 FROM articles WHERE feed_id = ?2 AND guid = ?1)
+        "#,
+        params![guid, feed_id, article.url],
+    )?;
+
+    let inserted = conn.execute(
+        r#"
+        INSERT OR IGNORE INTO articles (feed_id, guid, title, url, summary, content_html,
+                                        published_at, fetched_at, is_read, content_hash,
+                                        updated_at, thumbnail_url, backfilled)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?10, ?11, ?12)
+        "#,
+        params![
+            feed_id,
+            guid,
+            article.title,
+            article.url,
+            article.summary,
+            article.content_html,
+            article.published_at,
+            now,
+            content_hash(article),
+            article.updated_at,
+            article.thumbnail_url,
+            backfilled
+        ],
+    )?;
+
+    if inserted == 0 {
+        return Ok(None);
+    }
+
+    let id = conn.last_insert_rowid();
+    link_article_terms(conn, id, article)?;
+    store_article_media(conn, id, article)?;
+
+    Ok(Some(Article {
+        id,
+        feed_id,
+        title: article.title.clone(),
+        url: article.url.clone(),
+        summary: article.summary.clone(),
+        published_at: article.published_at,
+        fetched_at: now,
+        is_read: false,
+        read_at: None,
+        feed_title: None,
+        updated_at: article.updated_at,
+        revision: 0,
+        authors: article.authors.clone(),
+        categories: article.categories.clone(),
+        thumbnail_url: article.thumbnail_url.clone(),
+        starred: false,
+    }))
+}
+
+/// Mark the stored copy of an exported article read at `read_at` and
+/// starred, as requested, leaving state already set alone. The article is
+/// matched by its guid, or by its URL once a fetch has replaced the guid.
+/// Returns whether each was changed.
+fn merge_article_state(
+    conn: &Connection,
+    feed_id: i64,
+    article: &ParsedArticle,
+    read_at: Option<i64>,
+    starred: bool,
+) -> Result<(bool, bool), rusqlite::Error> {
+    let guid = article_guid(article);
+    if guid.is_empty() {
+        return Ok((false, false));
+    }
+
+    let marked_read = match read_at {
+        Some(read_at) => {
+            conn.execute(
+                "UPDATE articles SET is_read = 1, read_at = COALESCE(read_at, ?4)
+             WHERE feed_id = ?1 AND (guid = ?2 OR (?3 != '' AND url = ?3)) AND is_read = 0",
+                params![feed_id, guid, article.url, read_at],
+            )? > 0
+        }
+        None => false,
+    };
+
+    let newly_starred = starred
+        && conn.execute(
+            "UPDATE articles SET starred = 1
+             WHERE feed_id = ?1 AND (guid = ?2 OR (?3 != '' AND url = ?3)) AND starred = 0",
+            params![feed_id, guid, article.url],
+        )? > 0;
+
+    Ok((marked_read, newly_starred))
+}
+
+fn add_feed_to_folder(
+    conn: &Connection,
+    feed_id: i64,
+    folder_id: i64,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO feed_folders (feed_id, folder_id) VALUES (?1, ?2)",
+        params![feed_id, folder_id],
+    )?;
+    Ok(())
+}
+
+/// [`Database::file_feed`] on a locked connection or transaction
+fn file_feed(conn: &Connection, feed_id: i64, path: &[String]) -> Result<(), rusqlite::Error> {
+    let mut parent_id = None;
+    for name in path {
+        parent_id = Some(get_or_create_folder(conn, name, parent_id)?);
+    }
+
+    match parent_id {
+        Some(folder_id) => add_feed_to_folder(conn, feed_id, folder_id),
+        None => Ok(()),
+    }
+}
+
+/// Hash of the parts of an article a publisher might edit
+fn content_hash(article: &ParsedArticle) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        Some(article.title.as_str()),
+        article.summary.as_deref(),
+        article.content_html.as_deref(),
+    ] {
+        hasher.update(part.unwrap_or_default().as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
 pub struct Database {
     conn: Mutex<Connection>,
 }
@@ -62,64 +446,10 @@ impl Database {
         })
     }
 
+    /// Bring the schema up to date, see `migrations`
     pub fn run_migrations(&self) -> Result<(), PatinaError> {
-        let conn = self.conn.lock().unwrap();
-
-        conn.execute_batch(
-            r#"
-            CREATE TABLE IF NOT EXISTS feeds (
-                id INTEGER PRIMARY KEY,
-                title TEXT NOT NULL,
-                url TEXT NOT NULL UNIQUE,
-                site_url TEXT,
-                last_fetched_at INTEGER,
-                created_at INTEGER NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS articles (
-                id INTEGER PRIMARY KEY,
-                feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
-                title TEXT NOT NULL,
-                url TEXT NOT NULL,
-                summary TEXT,
-                published_at INTEGER,
-                fetched_at INTEGER NOT NULL,
-                is_read INTEGER DEFAULT 0,
-                read_at INTEGER,
-                UNIQUE(feed_id, url)
-            );
-
-            CREATE TABLE IF NOT EXISTS reading_patterns (
-                id INTEGER PRIMARY KEY,
-                pattern_type TEXT NOT NULL,
-                value TEXT NOT NULL,
-                source TEXT NOT NULL,
-                weight REAL DEFAULT 1.0,
-                created_at INTEGER NOT NULL,
-                UNIQUE(pattern_type, value)
-            );
-
-            CREATE TABLE IF NOT EXISTS article_topics (
-                article_id INTEGER REFERENCES articles(id) ON DELETE CASCADE,
-                topic TEXT NOT NULL,
-                score REAL,
-                PRIMARY KEY(article_id, topic)
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_articles_feed_id ON articles(feed_id);
-            CREATE INDEX IF NOT EXISTS idx_articles_is_read ON articles(is_read);
-            CREATE INDEX IF NOT EXISTS idx_articles_published_at ON articles(published_at);
-            CREATE INDEX IF NOT EXISTS idx_article_topics_topic ON article_topics(topic);
-
-            -- Composite index for efficient unread count per feed
-            CREATE INDEX IF NOT EXISTS idx_articles_feed_unread ON articles(feed_id, is_read) WHERE is_read = 0;
-
-            -- Composite index for sorting articles by date
-            CREATE INDEX IF NOT EXISTS idx_articles_date_sort ON articles(published_at DESC);
-            "#,
-        )?;
-
-        Ok(())
+        let mut conn = self.conn.lock().unwrap();
+        migrations::migrate(&mut conn)
     }
 
     // Feed operations
@@ -128,8 +458,23 @@ impl Database {
         let now = chrono::Utc::now().timestamp();
 
         conn.execute(
-            "INSERT INTO feeds (title, url, site_url, last_fetched_at, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![feed.title, feed.url, feed.site_url, now, now],
+            r#"
+            INSERT INTO feeds (title, url, site_url, last_fetched_at, created_at, etag, last_modified,
+                               last_success_at, poll_hints, format, icon_url)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?4, ?8, ?9, ?10)
+            "#,
+            params![
+                feed.title,
+                feed.url,
+                feed.site_url,
+                now,
+                now,
+                feed.etag,
+                feed.last_modified,
+                serde_json::to_string(&feed.poll_hints).ok(),
+                feed.format.map(|f| f.as_str()),
+                feed.icon_url
+            ],
         )?;
 
         let id = conn.last_insert_rowid();
@@ -142,20 +487,40 @@ impl Database {
             last_fetched_at: Some(now),
             created_at: now,
             unread_count: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            last_success_at: Some(now),
+            last_http_status: None,
+            next_fetch_at: None,
+            retired_at: None,
+            retired_reason: None,
+            format: feed.format,
+            icon_url: feed.icon_url.clone(),
+            pending: false,
         })
     }
 
+    /// Subscribe to a feed that hasn't been fetched yet, under a placeholder title
+    pub fn insert_pending_feed(
+        &self,
+        url: &str,
+        title: &str,
+        site_url: Option<&str>,
+    ) -> Result<Feed, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(insert_pending_feed(&conn, url, title, site_url)?)
+    }
+
     pub fn get_feed(&self, id: i64) -> Result<Option<Feed>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT f.id, f.title, f.url, f.site_url, f.last_fetched_at, f.created_at,
-                   (SELECT COUNT(*) FROM articles a WHERE a.feed_id = f.id AND a.is_read = 0) as unread_count
+            SELECT {FEED_COLUMNS}
             FROM feeds f
             WHERE f.id = ?1
-            "#,
-        )?;
+            "#
+        ))?;
 
         let feed = stmt.query_row(params![id], map_feed_row).optional()?;
 
@@ -165,16 +530,13 @@ impl Database {
     pub fn get_all_feeds(&self) -> Result<Vec<Feed>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        // Correlated subquery is efficient here because it uses the partial covering index
-        // idx_articles_feed_unread which only indexes unread articles
-        let mut stmt = conn.prepare_cached(
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-            SELECT f.id, f.title, f.url, f.site_url, f.last_fetched_at, f.created_at,
-                   (SELECT COUNT(*) FROM articles a WHERE a.feed_id = f.id AND a.is_read = 0) as unread_count
+            SELECT {FEED_COLUMNS}
             FROM feeds f
             ORDER BY f.title COLLATE NOCASE
-            "#,
-        )?;
+            "#
+        ))?;
 
         let feeds = stmt
             .query_map([], map_feed_row)?
@@ -192,14 +554,13 @@ impl Database {
     pub fn get_feed_by_url(&self, url: &str) -> Result<Option<Feed>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT f.id, f.title, f.url, f.site_url, f.last_fetched_at, f.created_at,
-                   (SELECT COUNT(*) FROM articles a WHERE a.feed_id = f.id AND a.is_read = 0) as unread_count
+            SELECT {FEED_COLUMNS}
             FROM feeds f
             WHERE f.url = ?1
-            "#,
-        )?;
+            "#
+        ))?;
 
         let feed = stmt.query_row(params![url], map_feed_row).optional()?;
 
@@ -211,84 +572,666 @@ impl Database {
         let now = chrono::Utc::now().timestamp();
 
         conn.execute(
-            "UPDATE feeds SET title = ?1, site_url = ?2, last_fetched_at = ?3 WHERE id = ?4",
-            params![feed.title, feed.site_url, now, id],
+            r#"
+            UPDATE feeds SET title = ?1, site_url = ?2, last_fetched_at = ?3, etag = ?4, last_modified = ?5,
+                             poll_hints = ?6, format = ?7, icon_url = COALESCE(?8, icon_url)
+            WHERE id = ?9
+            "#,
+            params![
+                feed.title,
+                feed.site_url,
+                now,
+                feed.etag,
+                feed.last_modified,
+                serde_json::to_string(&feed.poll_hints).ok(),
+                feed.format.map(|f| f.as_str()),
+                feed.icon_url,
+                id
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // Folder operations
+    pub fn create_folder(&self, name: &str, parent_id: Option<i64>) -> Result<Folder, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.execute(
+            "INSERT INTO folders (name, parent_id, created_at) VALUES (?1, ?2, ?3)",
+            params![name, parent_id, now],
+        )?;
+
+        Ok(Folder {
+            id: conn.last_insert_rowid(),
+            name: name.to_string(),
+            parent_id,
+            created_at: now,
+            unread_count: 0,
+            feed_count: 0,
+        })
+    }
+
+    /// Find a folder by name among a parent's subfolders, creating it if missing
+    pub fn get_or_create_folder(
+        &self,
+        name: &str,
+        parent_id: Option<i64>,
+    ) -> Result<i64, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(get_or_create_folder(&conn, name, parent_id)?)
+    }
+
+    pub fn get_folder(&self, id: i64) -> Result<Option<Folder>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            r#"
+            WITH RECURSIVE tree(id) AS (
+                SELECT ?1
+                UNION
+                SELECT fo.id FROM folders fo JOIN tree t ON fo.parent_id = t.id
+            ),
+            folder_feeds(feed_id) AS (
+                SELECT DISTINCT ff.feed_id FROM feed_folders ff JOIN tree t ON t.id = ff.folder_id
+            )
+            SELECT fo.id, fo.name, fo.parent_id, fo.created_at,
+                   (SELECT COUNT(*) FROM articles a
+                    WHERE a.feed_id IN folder_feeds AND a.is_read = 0 AND a.backfilled = 0),
+                   (SELECT COUNT(*) FROM folder_feeds)
+            FROM folders fo
+            WHERE fo.id = ?1
+            "#,
+        )?;
+
+        let folder = stmt.query_row(params![id], map_folder_row).optional()?;
+
+        Ok(folder)
+    }
+
+    /// Get every folder with the counts of its whole subtree
+    pub fn get_all_folders(&self) -> Result<Vec<Folder>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        // Pair each folder with itself and all of its descendants
+        let mut stmt = conn.prepare_cached(
+            r#"
+            WITH RECURSIVE tree(root_id, id) AS (
+                SELECT id, id FROM folders
+                UNION
+                SELECT t.root_id, fo.id FROM folders fo JOIN tree t ON fo.parent_id = t.id
+            ),
+            folder_feeds(root_id, feed_id) AS (
+                SELECT DISTINCT t.root_id, ff.feed_id
+                FROM tree t JOIN feed_folders ff ON ff.folder_id = t.id
+            )
+            SELECT fo.id, fo.name, fo.parent_id, fo.created_at,
+                   (SELECT COUNT(*) FROM folder_feeds ffs
+                    JOIN articles a ON a.feed_id = ffs.feed_id
+                    WHERE ffs.root_id = fo.id AND a.is_read = 0 AND a.backfilled = 0),
+                   (SELECT COUNT(*) FROM folder_feeds ffs WHERE ffs.root_id = fo.id)
+            FROM folders fo
+            ORDER BY fo.name COLLATE NOCASE
+            "#,
+        )?;
+
+        let folders = stmt
+            .query_map([], map_folder_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(folders)
+    }
+
+    pub fn rename_folder(&self, id: i64, name: &str) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE folders SET name = ?1 WHERE id = ?2",
+            params![name, id],
+        )?;
+        Ok(())
+    }
+
+    /// Move a folder under another one, or to the top level
+    pub fn move_folder(&self, id: i64, parent_id: Option<i64>) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        if let Some(parent_id) = parent_id {
+            // The new parent can't be the folder itself or one of its subfolders
+            let cycle = conn
+                .prepare(
+                    r#"
+                    WITH RECURSIVE tree(id) AS (
+                        SELECT ?1
+                        UNION
+                        SELECT fo.id FROM folders fo JOIN tree t ON fo.parent_id = t.id
+                    )
+                    SELECT 1 FROM tree WHERE id = ?2
+                    "#,
+                )?
+                .exists(params![id, parent_id])?;
+            if cycle {
+                return Err(PatinaError::DatabaseError(
+                    "A folder can't be moved into itself".to_string(),
+                ));
+            }
+        }
+
+        conn.execute(
+            "UPDATE folders SET parent_id = ?1 WHERE id = ?2",
+            params![parent_id, id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a folder and its subfolders; their feeds stay subscribed
+    pub fn delete_folder(&self, id: i64) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM folders WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn add_feed_to_folder(&self, feed_id: i64, folder_id: i64) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(add_feed_to_folder(&conn, feed_id, folder_id)?)
+    }
+
+    /// File a feed in the folder at `path`, creating the folders as needed.
+    /// An empty path leaves the feed at the top level.
+    pub fn file_feed(&self, feed_id: i64, path: &[String]) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(file_feed(&conn, feed_id, path)?)
+    }
+
+    pub fn remove_feed_from_folder(&self, feed_id: i64, folder_id: i64) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM feed_folders WHERE feed_id = ?1 AND folder_id = ?2",
+            params![feed_id, folder_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get the feeds filed directly in a folder
+    pub fn get_feeds_in_folder(&self, folder_id: i64) -> Result<Vec<Feed>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            r#"
+            SELECT {FEED_COLUMNS}
+            FROM feeds f
+            JOIN feed_folders ff ON ff.feed_id = f.id
+            WHERE ff.folder_id = ?1
+            ORDER BY f.title COLLATE NOCASE
+            "#
+        ))?;
+
+        let feeds = stmt
+            .query_map(params![folder_id], map_feed_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(feeds)
+    }
+
+    /// Every (feed_id, folder_id) pair of feeds filed in folders
+    pub fn get_folder_memberships(&self) -> Result<Vec<(i64, i64)>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let memberships = conn
+            .prepare("SELECT feed_id, folder_id FROM feed_folders ORDER BY feed_id, folder_id")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(memberships)
+    }
+
+    /// Get the cache validators to send on the next conditional fetch
+    pub fn get_feed_validators(&self, id: i64) -> Result<FeedValidators, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let validators = conn
+            .query_row(
+                "SELECT etag, last_modified FROM feeds WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(FeedValidators {
+                        etag: row.get(0)?,
+                        last_modified: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        validators.ok_or(PatinaError::NotFound)
+    }
+
+    /// Get the polling hints stored from the last full fetch
+    pub fn get_poll_hints(&self, id: i64) -> Result<PollHints, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let hints: Option<String> = conn
+            .query_row(
+                "SELECT poll_hints FROM feeds WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or(PatinaError::NotFound)?;
+
+        Ok(hints
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn get_feed_settings(&self, id: i64) -> Result<FeedSettings, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let settings = conn
+            .query_row(
+                "SELECT full_text_enabled, mark_updated_unread FROM feeds WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(FeedSettings {
+                        full_text_enabled: row.get(0)?,
+                        mark_updated_unread: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        settings.ok_or(PatinaError::NotFound)
+    }
+
+    pub fn update_feed_settings(
+        &self,
+        id: i64,
+        settings: &FeedSettings,
+    ) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let updated = conn.execute(
+            "UPDATE feeds SET full_text_enabled = ?1, mark_updated_unread = ?2 WHERE id = ?3",
+            params![settings.full_text_enabled, settings.mark_updated_unread, id],
+        )?;
+
+        if updated == 0 {
+            return Err(PatinaError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Store an icon found for a feed that doesn't declare one
+    pub fn set_feed_icon(&self, id: i64, icon_url: &str) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE feeds SET icon_url = ?1 WHERE id = ?2",
+            params![icon_url, id],
+        )?;
+        Ok(())
+    }
+
+    /// Point a subscription at the URL its feed has permanently moved to
+    pub fn update_feed_url(&self, id: i64, url: &str) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE feeds SET url = ?1 WHERE id = ?2", params![url, id])?;
+        Ok(())
+    }
+
+    /// Stop refreshing a feed that is gone for good, recording why
+    pub fn retire_feed(&self, id: i64, reason: &str) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.execute(
+            "UPDATE feeds SET retired_at = ?1, retired_reason = ?2 WHERE id = ?3",
+            params![now, reason, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Record a successful fetch, clearing any failure or retirement state
+    pub fn record_fetch_success(
+        &self,
+        id: i64,
+        http_status: Option<u16>,
+        next_fetch_at: i64,
+    ) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.execute(
+            r#"
+            UPDATE feeds
+            SET last_fetched_at = ?1, last_success_at = ?1, last_http_status = ?2,
+                consecutive_failures = 0, last_error = NULL, next_fetch_at = ?3,
+                retired_at = NULL, retired_reason = NULL, pending = 0
+            WHERE id = ?4
+            "#,
+            params![now, http_status, next_fetch_at, id],
         )?;
 
         Ok(())
     }
 
+    /// Record a failed fetch, returning the number of consecutive failures
+    pub fn record_fetch_failure(
+        &self,
+        id: i64,
+        error: &str,
+        http_status: Option<u16>,
+    ) -> Result<i32, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let failures = conn.query_row(
+            r#"
+            UPDATE feeds
+            SET consecutive_failures = consecutive_failures + 1, last_error = ?1,
+                last_http_status = ?2
+            WHERE id = ?3
+            RETURNING consecutive_failures
+            "#,
+            params![error, http_status, id],
+            |row| row.get(0),
+        )?;
+
+        Ok(failures)
+    }
+
+    /// Set the earliest time the feed should be fetched again by a background refresh
+    pub fn schedule_next_fetch(
+        &self,
+        id: i64,
+        next_fetch_at: Option<i64>,
+    ) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "UPDATE feeds SET next_fetch_at = ?1 WHERE id = ?2",
+            params![next_fetch_at, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get feeds that have failed at least `min_failures` times in a row
+    pub fn get_unhealthy_feeds(&self, min_failures: i32) -> Result<Vec<Feed>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT {FEED_COLUMNS}
+            FROM feeds f
+            WHERE f.consecutive_failures >= ?1
+            ORDER BY f.consecutive_failures DESC, f.title COLLATE NOCASE
+            "#
+        ))?;
+
+        let feeds = stmt
+            .query_map(params![min_failures], map_feed_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(feeds)
+    }
+
     // Article operations
+
+    /// Insert an article, returning `None` if the feed already has it
     pub fn insert_article(
         &self,
         feed_id: i64,
         article: &ParsedArticle,
-    ) -> Result<Article, PatinaError> {
+    ) -> Result<Option<Article>, PatinaError> {
+        self.insert_article_as(feed_id, article, false)
+    }
+
+    /// Insert an article from a feed's history. It starts unread, so
+    /// serendipity can surface it, but isn't counted or listed as new.
+    pub fn insert_backfilled_article(
+        &self,
+        feed_id: i64,
+        article: &ParsedArticle,
+    ) -> Result<Option<Article>, PatinaError> {
+        self.insert_article_as(feed_id, article, true)
+    }
+
+    fn insert_article_as(
+        &self,
+        feed_id: i64,
+        article: &ParsedArticle,
+        backfilled: bool,
+    ) -> Result<Option<Article>, PatinaError> {
         let conn = self.conn.lock().unwrap();
-        let now = chrono::Utc::now().timestamp();
+        Ok(insert_article(&conn, feed_id, article, backfilled)?)
+    }
 
+    /// Apply a publisher's edits to an article already stored under the same
+    /// guid, returning whether it changed. An entry whose `updated` time is no
+    /// newer than the stored one is taken at its word; otherwise the title,
+    /// summary and content are compared by hash. Changed articles can be
+    /// flagged unread again.
+    pub fn update_article(
+        &self,
+        feed_id: i64,
+        article: &ParsedArticle,
+        mark_unread: bool,
+    ) -> Result<bool, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let stored = conn
+            .query_row(
+                "SELECT id, content_hash, updated_at FROM articles WHERE feed_id = ?1 AND guid = ?2",
+                params![feed_id, article_guid(article)],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let Some((id, stored_hash, stored_updated_at)) = stored else {
+            return Ok(false);
+        };
+
+        // Media isn't part of the edit check; this also picks up enclosures
+        // of episodes stored before they were kept
+        store_article_media(&conn, id, article)?;
+
+        if let (Some(incoming), Some(stored)) = (article.updated_at, stored_updated_at) {
+            if incoming <= stored {
+                return Ok(false);
+            }
+        }
+
+        let hash = content_hash(article);
+        match stored_hash {
+            Some(stored_hash) if stored_hash == hash => return Ok(false),
+            // Stored before hashes were kept: nothing to compare against yet
+            None => {
+                conn.execute(
+                    "UPDATE articles SET content_hash = ?1 WHERE id = ?2",
+                    params![hash, id],
+                )?;
+                return Ok(false);
+            }
+            Some(_) => {}
+        }
+
+        let now = chrono::Utc::now().timestamp();
         conn.execute(
             r#"
-            INSERT OR IGNORE INTO articles (feed_id, title, url, summary, published_at, fetched_at, is_read)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)
+            UPDATE articles
+            SET title = ?1, summary = ?2, content_html = ?3, content_hash = ?4,
+                updated_at = ?5, revision = revision + 1, thumbnail_url = ?6,
+                is_read = CASE WHEN ?7 THEN 0 ELSE is_read END,
+                read_at = CASE WHEN ?7 THEN NULL ELSE read_at END
+            WHERE id = ?8
             "#,
             params![
-                feed_id,
                 article.title,
-                article.url,
                 article.summary,
-                article.published_at,
-                now
+                article.content_html,
+                hash,
+                article.updated_at.unwrap_or(now),
+                article.thumbnail_url,
+                mark_unread,
+                id
             ],
         )?;
+        link_article_terms(&conn, id, article)?;
 
-        let id = conn.last_insert_rowid();
-
-        Ok(Article {
-            id,
-            feed_id,
-            title: article.title.clone(),
-            url: article.url.clone(),
-            summary: article.summary.clone(),
-            published_at: article.published_at,
-            fetched_at: now,
-            is_read: false,
-            read_at: None,
-            feed_title: None,
-        })
+        Ok(true)
     }
 
     pub fn get_article(&self, id: i64) -> Result<Option<Article>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                   a.is_read, a.read_at, f.title as feed_title
+            SELECT {ARTICLE_COLUMNS}
             FROM articles a
             JOIN feeds f ON f.id = a.feed_id
             WHERE a.id = ?1
-            "#,
-        )?;
+            "#
+        ))?;
 
         let article = stmt.query_row(params![id], map_article_row).optional()?;
 
         Ok(article)
     }
 
+    /// Get the summary and full markup of an article for the reader
+    pub fn get_article_content(&self, id: i64) -> Result<Option<ArticleContent>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let content = conn
+            .query_row(
+                "SELECT id, summary, content_html, full_text_html FROM articles WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(ArticleContent {
+                        article_id: row.get(0)?,
+                        summary: row.get(1)?,
+                        content_html: row.get(2)?,
+                        full_text_html: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(content)
+    }
+
+    /// Store the main content extracted from an article's web page
+    pub fn set_article_full_text(&self, id: i64, html: &str) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE articles SET full_text_html = ?1 WHERE id = ?2",
+            params![html, id],
+        )?;
+        Ok(())
+    }
+
+    /// Get the media attached to an article
+    pub fn get_enclosures(&self, article_id: i64) -> Result<Vec<Enclosure>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, article_id, url, mime_type, length, duration_secs, local_path, downloaded_at
+            FROM enclosures
+            WHERE article_id = ?1
+            ORDER BY id
+            "#,
+        )?;
+
+        let enclosures = stmt
+            .query_map(params![article_id], map_enclosure_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(enclosures)
+    }
+
+    pub fn get_enclosure(&self, id: i64) -> Result<Option<Enclosure>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let enclosure = conn
+            .query_row(
+                r#"
+                SELECT id, article_id, url, mime_type, length, duration_secs, local_path,
+                       downloaded_at
+                FROM enclosures
+                WHERE id = ?1
+                "#,
+                params![id],
+                map_enclosure_row,
+            )
+            .optional()?;
+
+        Ok(enclosure)
+    }
+
+    /// Record where an enclosure was downloaded to, or `None` once the file is removed
+    pub fn set_enclosure_download(
+        &self,
+        id: i64,
+        local_path: Option<&str>,
+    ) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        let downloaded_at = local_path.map(|_| chrono::Utc::now().timestamp());
+
+        conn.execute(
+            "UPDATE enclosures SET local_path = ?1, downloaded_at = ?2 WHERE id = ?3",
+            params![local_path, downloaded_at, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_podcast_episode(
+        &self,
+        article_id: i64,
+    ) -> Result<Option<PodcastEpisode>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let episode = conn
+            .query_row(
+                r#"
+                SELECT episode, season, duration_secs, explicit, image_url
+                FROM podcast_episodes
+                WHERE article_id = ?1
+                "#,
+                params![article_id],
+                |row| {
+                    Ok(PodcastEpisode {
+                        episode: row.get(0)?,
+                        season: row.get(1)?,
+                        duration_secs: row.get(2)?,
+                        explicit: row.get(3)?,
+                        image_url: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(episode)
+    }
+
     pub fn get_articles_for_feed(&self, feed_id: i64) -> Result<Vec<Article>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare_cached(
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-            SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                   a.is_read, a.read_at, f.title as feed_title
+            SELECT {ARTICLE_COLUMNS}
             FROM articles a
             JOIN feeds f ON f.id = a.feed_id
             WHERE a.feed_id = ?1
             ORDER BY COALESCE(a.published_at, a.fetched_at) DESC
-            "#,
-        )?;
+            "#
+        ))?;
 
         let articles = stmt
             .query_map(params![feed_id], map_article_row)?
@@ -297,19 +1240,68 @@ impl Database {
         Ok(articles)
     }
 
+    /// Get articles credited to an author, newest first
+    pub fn get_articles_by_author(&self, author: &str) -> Result<Vec<Article>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            r#"
+            SELECT {ARTICLE_COLUMNS}
+            FROM articles a
+            JOIN feeds f ON f.id = a.feed_id
+            WHERE a.id IN (
+                SELECT aa.article_id FROM article_authors aa
+                JOIN authors au ON au.id = aa.author_id
+                WHERE au.name = ?1
+            )
+            ORDER BY COALESCE(a.published_at, a.fetched_at) DESC
+            "#
+        ))?;
+
+        let articles = stmt
+            .query_map(params![author], map_article_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
+    /// Get articles filed under a category or tag, newest first
+    pub fn get_articles_by_category(&self, category: &str) -> Result<Vec<Article>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            r#"
+            SELECT {ARTICLE_COLUMNS}
+            FROM articles a
+            JOIN feeds f ON f.id = a.feed_id
+            WHERE a.id IN (
+                SELECT ac.article_id FROM article_categories ac
+                JOIN categories c ON c.id = ac.category_id
+                WHERE c.name = ?1
+            )
+            ORDER BY COALESCE(a.published_at, a.fetched_at) DESC
+            "#
+        ))?;
+
+        let articles = stmt
+            .query_map(params![category], map_article_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
     pub fn get_all_unread_articles(&self) -> Result<Vec<Article>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare_cached(
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-            SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                   a.is_read, a.read_at, f.title as feed_title
+            SELECT {ARTICLE_COLUMNS}
             FROM articles a
             JOIN feeds f ON f.id = a.feed_id
-            WHERE a.is_read = 0
+            WHERE a.is_read = 0 AND a.backfilled = 0
             ORDER BY a.published_at IS NULL, a.published_at DESC
-            "#,
-        )?;
+            "#
+        ))?;
 
         let articles = stmt
             .query_map([], map_article_row)?
@@ -322,16 +1314,15 @@ impl Database {
     pub fn get_recent_articles(&self, limit: i32) -> Result<Vec<Article>, PatinaError> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare_cached(
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-            SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                   a.is_read, a.read_at, f.title as feed_title
+            SELECT {ARTICLE_COLUMNS}
             FROM articles a
             JOIN feeds f ON f.id = a.feed_id
             ORDER BY a.published_at IS NULL, a.published_at DESC
             LIMIT ?1
-            "#,
-        )?;
+            "#
+        ))?;
 
         let articles = stmt
             .query_map([limit], map_article_row)?
@@ -340,6 +1331,34 @@ impl Database {
         Ok(articles)
     }
 
+    /// Get articles of the feeds in a folder and its subfolders, newest first
+    pub fn get_articles_in_folder(&self, folder_id: i64) -> Result<Vec<Article>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            r#"
+            WITH RECURSIVE tree(id) AS (
+                SELECT ?1
+                UNION
+                SELECT fo.id FROM folders fo JOIN tree t ON fo.parent_id = t.id
+            )
+            SELECT {ARTICLE_COLUMNS}
+            FROM articles a
+            JOIN feeds f ON f.id = a.feed_id
+            WHERE a.feed_id IN (
+                SELECT ff.feed_id FROM feed_folders ff JOIN tree t ON t.id = ff.folder_id
+            )
+            ORDER BY COALESCE(a.published_at, a.fetched_at) DESC
+            "#
+        ))?;
+
+        let articles = stmt
+            .query_map(params![folder_id], map_article_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
     pub fn mark_article_read(&self, id: i64) -> Result<(), PatinaError> {
         let conn = self.conn.lock().unwrap();
         let now = chrono::Utc::now().timestamp();
@@ -356,13 +1375,122 @@ impl Database {
         let conn = self.conn.lock().unwrap();
 
         conn.execute(
-            "UPDATE articles SET is_read = 0, read_at = NULL WHERE id = ?1",
+            "UPDATE articles SET is_read = 0, read_at = NULL, backfilled = 0 WHERE id = ?1",
             params![id],
         )?;
 
         Ok(())
     }
 
+    pub fn set_article_starred(&self, id: i64, starred: bool) -> Result<(), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE articles SET starred = ?1 WHERE id = ?2",
+            params![starred, id],
+        )?;
+        Ok(())
+    }
+
+    /// Carry over read and starred state from another reader to the stored
+    /// copy of an exported article. State is only ever added, never cleared.
+    /// Returns whether the article was newly marked read and whether it was
+    /// newly starred.
+    pub fn merge_article_state(
+        &self,
+        feed_id: i64,
+        article: &ParsedArticle,
+        read_at: Option<i64>,
+        starred: bool,
+    ) -> Result<(bool, bool), PatinaError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(merge_article_state(
+            &conn, feed_id, article, read_at, starred,
+        )?)
+    }
+
+    /// Subscribe to the feeds of another reader's export and carry over its
+    /// articles with their read and starred state, in one transaction. New
+    /// feeds are pending until the next refresh. Only articles whose state
+    /// changed are counted as read or starred.
+    pub fn import_reader_export(
+        &self,
+        export: &ReaderExport,
+    ) -> Result<ReaderImportResult, PatinaError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut result = ReaderImportResult {
+            format: export.format,
+            feeds_added: 0,
+            articles_added: 0,
+            read_articles: 0,
+            starred_articles: 0,
+        };
+
+        let mut feed_ids = HashMap::new();
+        for exported in &export.feeds {
+            let existing = tx
+                .query_row(
+                    "SELECT id FROM feeds WHERE url = ?1",
+                    params![exported.url],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let feed_id = match existing {
+                Some(id) => id,
+                None => {
+                    let title = exported.title.as_deref().unwrap_or(&exported.url);
+                    result.feeds_added += 1;
+                    insert_pending_feed(&tx, &exported.url, title, exported.site_url.as_deref())?.id
+                }
+            };
+            file_feed(&tx, feed_id, &exported.folder)?;
+            feed_ids.insert(exported.url.as_str(), feed_id);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        for item in &export.items {
+            let Some(&feed_id) = feed_ids.get(item.feed_url.as_str()) else {
+                continue;
+            };
+            if insert_article(&tx, feed_id, &item.article, false)?.is_some() {
+                result.articles_added += 1;
+            }
+            let (marked_read, starred) = merge_article_state(
+                &tx,
+                feed_id,
+                &item.article,
+                item.is_read.then_some(now),
+                item.starred,
+            )?;
+            result.read_articles += i32::from(marked_read);
+            result.starred_articles += i32::from(starred);
+        }
+
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// Get starred articles, newest first
+    pub fn get_starred_articles(&self) -> Result<Vec<Article>, PatinaError> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            r#"
+            SELECT {ARTICLE_COLUMNS}
+            FROM articles a
+            JOIN feeds f ON f.id = a.feed_id
+            WHERE a.starred = 1
+            ORDER BY COALESCE(a.published_at, a.fetched_at) DESC
+            "#
+        ))?;
+
+        let articles = stmt
+            .query_map([], map_article_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
     // Reading patterns
     pub fn get_reading_patterns(&self) -> Result<Vec<ReadingPattern>, PatinaError> {
         let conn = self.conn.lock().unwrap();
@@ -455,17 +1583,16 @@ impl Database {
 
         if topics.is_empty() {
             // No patterns, return random unread articles
-            let mut stmt = conn.prepare_cached(
+            let mut stmt = conn.prepare_cached(&format!(
                 r#"
-                SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                       a.is_read, a.read_at, f.title as feed_title
+                SELECT {ARTICLE_COLUMNS}
                 FROM articles a
                 JOIN feeds f ON f.id = a.feed_id
                 WHERE a.is_read = 0
                 ORDER BY RANDOM()
                 LIMIT ?1
-                "#,
-            )?;
+                "#
+            ))?;
 
             let articles = stmt
                 .query_map(params![limit], map_article_row)?
@@ -477,10 +1604,9 @@ impl Database {
         // Use JSON array with json_each() - avoids temp table overhead and allows caching
         let topics_json = serde_json::to_string(topics).unwrap_or_else(|_| "[]".to_string());
 
-        let mut stmt = conn.prepare_cached(
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-            SELECT a.id, a.feed_id, a.title, a.url, a.summary, a.published_at, a.fetched_at,
-                   a.is_read, a.read_at, f.title as feed_title,
+            SELECT {ARTICLE_COLUMNS},
                    COALESCE(topic_scores.total_score, 0) as topic_score
             FROM articles a
             JOIN feeds f ON f.id = a.feed_id
@@ -493,8 +1619,8 @@ impl Database {
             WHERE a.is_read = 0
             ORDER BY topic_score DESC, RANDOM()
             LIMIT ?1
-            "#,
-        )?;
+            "#
+        ))?;
 
         let articles = stmt
             .query_map(params![limit, topics_json], map_article_row)?
@@ -540,3 +1666,492 @@ impl<T> OptionalExt<T> for Result<T, rusqlite::Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::test_db::database_with_feed;
+    use crate::storage::models::{ExportedItem, OpmlFeed, ParsedEnclosure, ReaderExportFormat};
+
+    fn article(guid: &str, url: &str) -> ParsedArticle {
+        ParsedArticle {
+            guid: guid.to_string(),
+            title: "Title".to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_migrate_articles_to_guid() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.db");
+
+        // A database from before guids, with the original URL-keyed articles table
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                r#"
+                CREATE TABLE feeds (
+                    id INTEGER PRIMARY KEY, title TEXT NOT NULL, url TEXT NOT NULL UNIQUE,
+                    site_url TEXT, last_fetched_at INTEGER, created_at INTEGER NOT NULL
+                );
+                CREATE TABLE articles (
+                    id INTEGER PRIMARY KEY,
+                    feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                    title TEXT NOT NULL, url TEXT NOT NULL, summary TEXT, published_at INTEGER,
+                    fetched_at INTEGER NOT NULL, is_read INTEGER DEFAULT 0, read_at INTEGER,
+                    UNIQUE(feed_id, url)
+                );
+                CREATE TABLE article_topics (
+                    article_id INTEGER REFERENCES articles(id) ON DELETE CASCADE,
+                    topic TEXT NOT NULL, score REAL, PRIMARY KEY(article_id, topic)
+                );
+                INSERT INTO feeds VALUES (1, 'Example', 'https://example.com/feed.xml', NULL, 0, 0);
+                INSERT INTO articles VALUES (1, 1, 'Old', 'https://example.com/1', NULL, NULL, 0, 1, 5);
+                INSERT INTO article_topics VALUES (1, 'rust', 0.5);
+                "#,
+            )
+            .unwrap();
+        }
+
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        db.run_migrations().unwrap();
+        // Running again is a no-op
+        db.run_migrations().unwrap();
+
+        let stored = db.get_article(1).unwrap().unwrap();
+        assert!(stored.is_read);
+        assert_eq!(stored.read_at, Some(5));
+
+        // The first sight of the real guid claims the migrated row
+        assert!(
+            db.insert_article(1, &article("post-1", "https://example.com/1"))
+                .unwrap()
+                .is_none()
+        );
+        // ...so later fetches with tracking parameters are still the same entry
+        assert!(
+            db.insert_article(
+                1,
+                &article("post-1", "https://example.com/1?utm_source=rss")
+            )
+            .unwrap()
+            .is_none()
+        );
+        assert_eq!(db.get_articles_for_feed(1).unwrap().len(), 1);
+
+        // Topics and their cascade survived the rebuild
+        let conn = db.conn.lock().unwrap();
+        let topics: i64 = conn
+            .query_row("SELECT COUNT(*) FROM article_topics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(topics, 1);
+        conn.execute("DELETE FROM articles", []).unwrap();
+        let topics: i64 = conn
+            .query_row("SELECT COUNT(*) FROM article_topics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(topics, 0);
+    }
+
+    #[test]
+    fn test_insert_article_identity() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        assert!(
+            db.insert_article(feed_id, &article("a", "https://example.com/a"))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            db.insert_article(feed_id, &article("a", "https://example.com/a?ref=x"))
+                .unwrap()
+                .is_none()
+        );
+
+        // Link-less entries with distinct guids are all kept
+        assert!(
+            db.insert_article(feed_id, &article("sha256:1", ""))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            db.insert_article(feed_id, &article("sha256:2", ""))
+                .unwrap()
+                .is_some()
+        );
+
+        // Without a guid the URL identifies the entry
+        assert!(
+            db.insert_article(feed_id, &article("", "https://example.com/b"))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            db.insert_article(feed_id, &article("", "https://example.com/b"))
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(db.get_articles_for_feed(feed_id).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_merge_article_state() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        // Imported without a guid, then picked up by a fetch
+        let imported = db
+            .insert_article(feed_id, &article("", "https://example.com/a"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            db.merge_article_state(
+                feed_id,
+                &article("", "https://example.com/a"),
+                Some(100),
+                true
+            )
+            .unwrap(),
+            (true, true)
+        );
+        assert_eq!(
+            db.merge_article_state(
+                1,
+                &article("", "https://example.com/missing"),
+                Some(100),
+                true
+            )
+            .unwrap(),
+            (false, false)
+        );
+        db.insert_article(feed_id, &article("tag:a", "https://example.com/a"))
+            .unwrap();
+
+        let stored = db.get_article(imported.id).unwrap().unwrap();
+        assert!(stored.is_read && stored.starred);
+        assert_eq!(stored.read_at, Some(100));
+
+        // Merging never clears state the reader set here, and state already
+        // set isn't reported as changed
+        assert_eq!(
+            db.merge_article_state(feed_id, &article("", "https://example.com/a"), None, false)
+                .unwrap(),
+            (false, false)
+        );
+        assert_eq!(
+            db.merge_article_state(
+                feed_id,
+                &article("", "https://example.com/a"),
+                Some(200),
+                true
+            )
+            .unwrap(),
+            (false, false)
+        );
+        let stored = db.get_article(imported.id).unwrap().unwrap();
+        assert!(stored.is_read && stored.starred);
+
+        db.set_article_starred(imported.id, false).unwrap();
+        assert!(db.get_starred_articles().unwrap().is_empty());
+        assert_eq!(db.get_articles_for_feed(feed_id).unwrap().len(), 1);
+
+        // Link-less articles share an empty URL, so only their guid matches
+        let first = db
+            .insert_article(feed_id, &article("hash:1", ""))
+            .unwrap()
+            .unwrap();
+        let second = db
+            .insert_article(feed_id, &article("hash:2", ""))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            db.merge_article_state(feed_id, &article("", ""), Some(100), true)
+                .unwrap(),
+            (false, false)
+        );
+        assert_eq!(
+            db.merge_article_state(feed_id, &article("hash:1", ""), Some(100), false)
+                .unwrap(),
+            (true, false)
+        );
+        assert!(db.get_article(first.id).unwrap().unwrap().is_read);
+        assert!(!db.get_article(second.id).unwrap().unwrap().is_read);
+    }
+
+    #[test]
+    fn test_import_reader_export() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        db.run_migrations().unwrap();
+
+        let feed = |url: &str| OpmlFeed {
+            url: url.to_string(),
+            title: None,
+            site_url: None,
+            folder: vec!["News".to_string()],
+        };
+        let item = |feed_url: &str, url: &str| ExportedItem {
+            feed_url: feed_url.to_string(),
+            article: article("", url),
+            is_read: true,
+            starred: false,
+        };
+        let export = ReaderExport {
+            format: ReaderExportFormat::Feedbin,
+            feeds: vec![
+                feed("https://a.example/feed"),
+                feed("https://b.example/feed"),
+            ],
+            items: vec![
+                item("https://a.example/feed", "https://a.example/1"),
+                item("https://b.example/feed", "https://b.example/1"),
+            ],
+        };
+
+        // A failed import leaves nothing behind
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER fail_import BEFORE INSERT ON articles
+                 WHEN NEW.url = 'https://b.example/1' BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+            )
+            .unwrap();
+        assert!(db.import_reader_export(&export).is_err());
+        assert!(db.get_all_feeds().unwrap().is_empty());
+        assert!(db.get_all_folders().unwrap().is_empty());
+
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("DROP TRIGGER fail_import")
+            .unwrap();
+        let result = db.import_reader_export(&export).unwrap();
+        assert_eq!((result.feeds_added, result.articles_added), (2, 2));
+        assert_eq!((result.read_articles, result.starred_articles), (2, 0));
+
+        // Importing again changes nothing, so nothing is counted
+        let result = db.import_reader_export(&export).unwrap();
+        assert_eq!((result.feeds_added, result.articles_added), (0, 0));
+        assert_eq!((result.read_articles, result.starred_articles), (0, 0));
+    }
+
+    #[test]
+    fn test_update_article() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        let original = ParsedArticle {
+            summary: Some("First draft".to_string()),
+            updated_at: Some(100),
+            ..article("a", "https://example.com/a")
+        };
+        let id = db.insert_article(feed_id, &original).unwrap().unwrap().id;
+        db.mark_article_read(id).unwrap();
+
+        // Same content is not an update
+        assert!(!db.update_article(feed_id, &original, true).unwrap());
+
+        // An entry claiming no newer update time is trusted
+        let stale = ParsedArticle {
+            summary: Some("Edited".to_string()),
+            ..original.clone()
+        };
+        assert!(!db.update_article(feed_id, &stale, true).unwrap());
+
+        // A correction is applied in place and can flag the article unread
+        let edited = ParsedArticle {
+            updated_at: Some(200),
+            ..stale.clone()
+        };
+        assert!(db.update_article(feed_id, &edited, true).unwrap());
+        let stored = db.get_article(id).unwrap().unwrap();
+        assert_eq!(stored.summary.as_deref(), Some("Edited"));
+        assert_eq!(stored.updated_at, Some(200));
+        assert_eq!(stored.revision, 1);
+        assert!(!stored.is_read);
+
+        // Without update times the content hash decides, and read state is kept
+        db.mark_article_read(id).unwrap();
+        let undated = ParsedArticle {
+            title: "New title".to_string(),
+            updated_at: None,
+            ..edited
+        };
+        assert!(db.update_article(feed_id, &undated, false).unwrap());
+        let stored = db.get_article(id).unwrap().unwrap();
+        assert_eq!(stored.title, "New title");
+        assert_eq!(stored.revision, 2);
+        assert!(stored.is_read);
+
+        // Unknown articles are left to insert_article
+        assert!(
+            !db.update_article(feed_id, &article("b", "https://example.com/b"), false)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_article_authors_and_categories() {
+        let (db, feed_id) = database_with_feed("https://example.com/feed.xml");
+
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let first = ParsedArticle {
+            authors: names(&["Zoe Writer", "Adam Editor"]),
+            categories: names(&["Politics", "Local"]),
+            ..article("a", "https://example.com/a")
+        };
+        let second = ParsedArticle {
+            authors: names(&["zoe writer"]),
+            categories: names(&["Sport"]),
+            ..article("b", "https://example.com/b")
+        };
+        db.insert_article(feed_id, &first).unwrap();
+        db.insert_article(feed_id, &second).unwrap();
+
+        // Bylines keep feed order
+        let stored = db.get_article(1).unwrap().unwrap();
+        assert_eq!(stored.authors, vec!["Zoe Writer", "Adam Editor"]);
+        assert_eq!(stored.categories, vec!["Politics", "Local"]);
+
+        // Names match regardless of case
+        assert_eq!(db.get_articles_by_author("ZOE WRITER").unwrap().len(), 2);
+        assert_eq!(db.get_articles_by_author("Adam Editor").unwrap().len(), 1);
+        assert_eq!(db.get_articles_by_category("sport").unwrap().len(), 1);
+        assert!(db.get_articles_by_category("Weather").unwrap().is_empty());
+
+        // Edits replace the links
+        let edited = ParsedArticle {
+            title: "Corrected".to_string(),
+            authors: names(&["Adam Editor"]),
+            ..first
+        };
+        assert!(db.update_article(feed_id, &edited, false).unwrap());
+        assert_eq!(db.get_articles_by_author("Zoe Writer").unwrap().len(), 1);
+        assert_eq!(
+            db.get_article(1).unwrap().unwrap().authors,
+            vec!["Adam Editor"]
+        );
+    }
+
+    #[test]
+    fn test_folders() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(temp_dir.path().join("test.db").to_str().unwrap()).unwrap();
+        db.run_migrations().unwrap();
+        for url in ["https://a.example/feed", "https://b.example/feed"] {
+            db.insert_feed(&ParsedFeed {
+                title: url.to_string(),
+                url: url.to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        db.insert_article(1, &article("a1", "https://a.example/1"))
+            .unwrap();
+        db.insert_article(1, &article("a2", "https://a.example/2"))
+            .unwrap();
+        db.insert_article(2, &article("b1", "https://b.example/1"))
+            .unwrap();
+
+        let tech = db.create_folder("Tech", None).unwrap();
+        let rust = db.get_or_create_folder("Rust", Some(tech.id)).unwrap();
+        assert_eq!(
+            db.get_or_create_folder("rust", Some(tech.id)).unwrap(),
+            rust
+        );
+        let top_level_rust = db.get_or_create_folder("Rust", None).unwrap();
+        assert_ne!(top_level_rust, rust);
+        assert!(db.create_folder("Tech", None).is_err());
+
+        // A feed can sit in several folders but is only counted once per subtree
+        db.add_feed_to_folder(1, tech.id).unwrap();
+        db.add_feed_to_folder(1, rust).unwrap();
+        db.add_feed_to_folder(2, rust).unwrap();
+        db.add_feed_to_folder(2, rust).unwrap();
+
+        let counts = |id| {
+            let folder = db.get_folder(id).unwrap().unwrap();
+            (folder.unread_count, folder.feed_count)
+        };
+        assert_eq!(counts(tech.id), (3, 2));
+        assert_eq!(counts(rust), (3, 2));
+        assert_eq!(db.get_articles_in_folder(tech.id).unwrap().len(), 3);
+        assert_eq!(db.get_feeds_in_folder(tech.id).unwrap().len(), 1);
+
+        db.mark_article_read(1).unwrap();
+        db.remove_feed_from_folder(1, rust).unwrap();
+        assert_eq!(counts(rust), (1, 1));
+        assert_eq!(counts(tech.id), (2, 2));
+
+        let all = db.get_all_folders().unwrap();
+        let tech_folder = all.iter().find(|f| f.id == tech.id).unwrap();
+        assert_eq!((tech_folder.unread_count, tech_folder.feed_count), (2, 2));
+
+        // Folders can't be moved into their own subtree
+        assert!(db.move_folder(tech.id, Some(rust)).is_err());
+        assert!(db.move_folder(tech.id, Some(tech.id)).is_err());
+        assert!(db.move_folder(rust, None).is_err());
+        db.delete_folder(top_level_rust).unwrap();
+        db.move_folder(rust, None).unwrap();
+        assert_eq!(counts(tech.id), (1, 1));
+
+        // Deleting a folder takes its subfolders, not its feeds
+        db.move_folder(rust, Some(tech.id)).unwrap();
+        db.delete_folder(tech.id).unwrap();
+        assert!(db.get_folder(rust).unwrap().is_none());
+        assert_eq!(db.get_all_feeds().unwrap().len(), 2);
+        assert!(db.get_folder_memberships().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_article_enclosures() {
+        let (db, feed_id) = database_with_feed("https://example.com/podcast.xml");
+
+        let episode = ParsedArticle {
+            enclosures: vec![ParsedEnclosure {
+                url: "https://cdn.example.com/1.mp3".to_string(),
+                mime_type: Some("audio/mpeg".to_string()),
+                length: Some(1234),
+                duration_secs: Some(1800),
+            }],
+            podcast: Some(PodcastEpisode {
+                episode: Some(1),
+                explicit: Some(false),
+                ..Default::default()
+            }),
+            ..article("ep1", "https://example.com/1")
+        };
+        let id = db.insert_article(feed_id, &episode).unwrap().unwrap().id;
+
+        let enclosures = db.get_enclosures(id).unwrap();
+        assert_eq!(enclosures.len(), 1);
+        assert_eq!(enclosures[0].length, Some(1234));
+        assert!(enclosures[0].local_path.is_none());
+        assert_eq!(
+            db.get_podcast_episode(id).unwrap().unwrap().explicit,
+            Some(false)
+        );
+
+        db.set_enclosure_download(enclosures[0].id, Some("/tmp/1.mp3"))
+            .unwrap();
+
+        // Seeing the episode again refreshes the metadata but keeps the download
+        let mut again = episode.clone();
+        again.enclosures[0].length = Some(4321);
+        db.update_article(feed_id, &again, false).unwrap();
+
+        let enclosure = db.get_enclosure(enclosures[0].id).unwrap().unwrap();
+        assert_eq!(enclosure.length, Some(4321));
+        assert_eq!(enclosure.local_path.as_deref(), Some("/tmp/1.mp3"));
+        assert!(enclosure.downloaded_at.is_some());
+
+        db.set_enclosure_download(enclosure.id, None).unwrap();
+        assert!(
+            db.get_enclosure(enclosure.id)
+                .unwrap()
+                .unwrap()
+                .local_path
+                .is_none()
+        );
+    }
+}
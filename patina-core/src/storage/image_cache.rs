@@ -0,0 +1,174 @@
+use crate::PatinaError;
+use crate::feed::http::create_client;
+use crate::storage::models::ImageCacheOptions;
+use reqwest::header::CONTENT_TYPE;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Largest single image the cache will store
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Local copies of feed icons and thumbnails, so they show offline.
+///
+/// Files are named by a hash of their URL. Reading an image marks it as
+/// recently used, and the least recently used ones are evicted whenever the
+/// cache grows past its size limit.
+#[derive(Debug, Clone)]
+pub struct ImageCache {
+    directory: PathBuf,
+    max_bytes: u64,
+}
+
+impl ImageCache {
+    pub fn new(options: &ImageCacheOptions) -> Self {
+        Self {
+            directory: PathBuf::from(&options.directory),
+            max_bytes: options.max_bytes,
+        }
+    }
+
+    /// Path of the cached copy of an image, without touching the network
+    pub fn get(&self, url: &str) -> Option<PathBuf> {
+        let path = self.path_for(url);
+
+        // The modification time doubles as the last use
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .ok()?;
+
+        Some(path)
+    }
+
+    /// Path of the cached copy of an image, downloading it first if needed
+    pub fn fetch(&self, url: &str) -> Result<PathBuf, PatinaError> {
+        if let Some(path) = self.get(url) {
+            return Ok(path);
+        }
+
+        let client = create_client()?;
+        let response = client.get(url).send()?.error_for_status()?;
+
+        let is_image = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|t| t.trim().to_ascii_lowercase().starts_with("image/"));
+        if !is_image {
+            return Err(PatinaError::ParseError(format!("Not an image: {url}")));
+        }
+
+        let limit = MAX_IMAGE_BYTES.min(self.max_bytes);
+        if response
+            .content_length()
+            .is_some_and(|length| length > limit)
+        {
+            return Err(PatinaError::IoError(format!("Image too large: {url}")));
+        }
+        let bytes = response.bytes()?;
+        if bytes.len() as u64 > limit {
+            return Err(PatinaError::IoError(format!("Image too large: {url}")));
+        }
+
+        fs::create_dir_all(&self.directory)?;
+        let path = self.path_for(url);
+        let partial = path.with_extension("tmp");
+        fs::write(&partial, &bytes)?;
+        fs::rename(&partial, &path)?;
+
+        self.evict(&path)?;
+        Ok(path)
+    }
+
+    /// Delete every cached image
+    pub fn clear(&self) -> Result<(), PatinaError> {
+        match fs::remove_dir_all(&self.directory) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        let hash = Sha256::digest(url.as_bytes());
+        self.directory.join(format!("{:x}", hash))
+    }
+
+    /// Remove the least recently used images until the cache fits its limit,
+    /// never removing `keep`
+    fn evict(&self, keep: &Path) -> Result<(), PatinaError> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        files.sort();
+
+        for (_, len, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if path != keep {
+                fs::remove_file(&path)?;
+                total -= len;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn cache(directory: &Path, max_bytes: u64) -> ImageCache {
+        ImageCache::new(&ImageCacheOptions {
+            directory: directory.to_string_lossy().into_owned(),
+            max_bytes,
+        })
+    }
+
+    /// Put an image in the cache as if it was downloaded `age_secs` ago
+    fn store(cache: &ImageCache, url: &str, len: usize, age_secs: u64) -> PathBuf {
+        let path = cache.path_for(url);
+        fs::write(&path, vec![0u8; len]).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), 250);
+
+        let oldest = store(&cache, "https://example.com/a.png", 100, 300);
+        let used = store(&cache, "https://example.com/b.png", 100, 200);
+        let newest = store(&cache, "https://example.com/c.png", 100, 100);
+
+        // Reading an image counts as using it
+        assert_eq!(cache.get("https://example.com/b.png"), Some(used.clone()));
+        assert!(cache.get("https://example.com/missing.png").is_none());
+
+        cache.evict(&newest).unwrap();
+        assert!(!oldest.exists());
+        assert!(used.exists());
+        assert!(newest.exists());
+
+        cache.clear().unwrap();
+        assert!(cache.get("https://example.com/b.png").is_none());
+    }
+}
@@ -0,0 +1,679 @@
+use crate::PatinaError;
+use rusqlite::{Connection, Transaction, params};
+
+/// A step from one schema version to the next
+struct Migration {
+    /// Schema version once the step has run, stored in `PRAGMA user_version`
+    version: i32,
+    description: &'static str,
+    /// Rebuilds tables. The database is backed up before the run, and foreign
+    /// keys are off while the step runs.
+    destructive: bool,
+    up: fn(&Transaction) -> Result<(), rusqlite::Error>,
+}
+
+/// Every schema change, in order. Append new steps; never edit a released one.
+///
+/// Databases from before versioning are at version 0 with any prefix of
+/// steps 1-11 already applied, so those steps check before changing anything.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema",
+        destructive: false,
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Feed fetch state, health and scheduling",
+        destructive: false,
+        up: |tx| {
+            add_columns(
+                tx,
+                &[
+                    ("feeds", "etag", "TEXT"),
+                    ("feeds", "last_modified", "TEXT"),
+                    (
+                        "feeds",
+                        "consecutive_failures",
+                        "INTEGER NOT NULL DEFAULT 0",
+                    ),
+                    ("feeds", "last_error", "TEXT"),
+                    ("feeds", "last_success_at", "INTEGER"),
+                    ("feeds", "last_http_status", "INTEGER"),
+                    ("feeds", "next_fetch_at", "INTEGER"),
+                    ("feeds", "poll_hints", "TEXT"),
+                    ("feeds", "retired_at", "INTEGER"),
+                    ("feeds", "retired_reason", "TEXT"),
+                ],
+            )
+        },
+    },
+    Migration {
+        version: 3,
+        description: "Article markup, full text and feed formats",
+        destructive: false,
+        up: |tx| {
+            add_columns(
+                tx,
+                &[
+                    ("articles", "content_html", "TEXT"),
+                    ("feeds", "full_text_enabled", "INTEGER NOT NULL DEFAULT 0"),
+                    ("articles", "full_text_html", "TEXT"),
+                    ("feeds", "format", "TEXT"),
+                ],
+            )
+        },
+    },
+    Migration {
+        version: 4,
+        description: "Key articles by guid instead of URL",
+        destructive: true,
+        up: key_articles_by_guid,
+    },
+    Migration {
+        version: 5,
+        description: "Article edits",
+        destructive: false,
+        up: |tx| {
+            add_columns(
+                tx,
+                &[
+                    ("feeds", "mark_updated_unread", "INTEGER NOT NULL DEFAULT 0"),
+                    ("articles", "content_hash", "TEXT"),
+                    ("articles", "updated_at", "INTEGER"),
+                    ("articles", "revision", "INTEGER NOT NULL DEFAULT 0"),
+                ],
+            )
+        },
+    },
+    Migration {
+        version: 6,
+        description: "Article authors and categories",
+        destructive: false,
+        up: |tx| {
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS authors (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+                );
+
+                CREATE TABLE IF NOT EXISTS article_authors (
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY(article_id, author_id)
+                );
+
+                CREATE TABLE IF NOT EXISTS categories (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+                );
+
+                CREATE TABLE IF NOT EXISTS article_categories (
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY(article_id, category_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_article_authors_author ON article_authors(author_id);
+                CREATE INDEX IF NOT EXISTS idx_article_categories_category ON article_categories(category_id);
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 7,
+        description: "Enclosures and podcast episodes",
+        destructive: false,
+        up: |tx| {
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS enclosures (
+                    id INTEGER PRIMARY KEY,
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    url TEXT NOT NULL,
+                    mime_type TEXT,
+                    length INTEGER,
+                    duration_secs INTEGER,
+                    local_path TEXT,
+                    downloaded_at INTEGER,
+                    UNIQUE(article_id, url)
+                );
+
+                CREATE TABLE IF NOT EXISTS podcast_episodes (
+                    article_id INTEGER PRIMARY KEY REFERENCES articles(id) ON DELETE CASCADE,
+                    episode INTEGER,
+                    season INTEGER,
+                    duration_secs INTEGER,
+                    explicit INTEGER,
+                    image_url TEXT
+                );
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 8,
+        description: "Feed icons, article thumbnails and backfilled history",
+        destructive: false,
+        up: |tx| {
+            add_columns(
+                tx,
+                &[
+                    ("feeds", "icon_url", "TEXT"),
+                    ("articles", "thumbnail_url", "TEXT"),
+                    ("articles", "backfilled", "INTEGER NOT NULL DEFAULT 0"),
+                ],
+            )
+        },
+    },
+    Migration {
+        version: 9,
+        description: "Folders",
+        destructive: false,
+        up: |tx| {
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS folders (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL COLLATE NOCASE,
+                    parent_id INTEGER REFERENCES folders(id) ON DELETE CASCADE,
+                    created_at INTEGER NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS feed_folders (
+                    feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                    folder_id INTEGER NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+                    PRIMARY KEY(feed_id, folder_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_feed_folders_folder ON feed_folders(folder_id);
+
+                -- Folder names are unique among siblings; top-level folders have no parent to compare
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_folders_parent_name ON folders(IFNULL(parent_id, 0), name);
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 10,
+        description: "Pending subscriptions",
+        destructive: false,
+        up: |tx| add_columns(tx, &[("feeds", "pending", "INTEGER NOT NULL DEFAULT 0")]),
+    },
+    Migration {
+        version: 11,
+        description: "Starred articles",
+        destructive: false,
+        up: |tx| add_columns(tx, &[("articles", "starred", "INTEGER NOT NULL DEFAULT 0")]),
+    },
+];
+
+/// Indexes on the articles table, recreated when it is rebuilt
+const ARTICLE_INDEXES: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_articles_feed_id ON articles(feed_id);
+    CREATE INDEX IF NOT EXISTS idx_articles_is_read ON articles(is_read);
+    CREATE INDEX IF NOT EXISTS idx_articles_published_at ON articles(published_at);
+
+    -- Composite index for efficient unread count per feed
+    CREATE INDEX IF NOT EXISTS idx_articles_feed_unread ON articles(feed_id, is_read) WHERE is_read = 0;
+
+    -- Composite index for sorting articles by date
+    CREATE INDEX IF NOT EXISTS idx_articles_date_sort ON articles(published_at DESC);
+    "#;
+
+/// Version a database reaches once every migration has run
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Bring the database up to the latest schema version
+pub fn migrate(conn: &mut Connection) -> Result<(), PatinaError> {
+    migrate_to(conn, latest_version())
+}
+
+/// Run the migrations after the database's version up to `target`, each in
+/// its own transaction. A failed step leaves the database at the version
+/// before it.
+fn migrate_to(conn: &mut Connection, target: i32) -> Result<(), PatinaError> {
+    let current = schema_version(conn)?;
+    if current > latest_version() {
+        return Err(PatinaError::DatabaseError(format!(
+            "Database schema version {current} is newer than this version of Patina supports"
+        )));
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > current && migration.version <= target)
+        .collect();
+
+    // Keep the database as it was before the run; a new one has nothing to lose
+    let existing = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'feeds'")?
+        .exists([])?;
+    if existing && pending.iter().any(|migration| migration.destructive) {
+        backup(conn, current)?;
+    }
+
+    for migration in pending {
+        apply(conn, migration).map_err(|e| {
+            PatinaError::DatabaseError(format!(
+                "Migration {} ({}) failed: {}",
+                migration.version, migration.description, e
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> Result<i32, rusqlite::Error> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn apply(conn: &mut Connection, migration: &Migration) -> Result<(), rusqlite::Error> {
+    // SQLite can't change constraints in place, so rebuilds move the old table
+    // aside and copy its rows. Foreign keys can only be switched off outside a
+    // transaction, and the legacy rename leaves other tables' references to
+    // the moved table alone.
+    if migration.destructive {
+        conn.pragma_update(None, "foreign_keys", "OFF")?;
+        conn.pragma_update(None, "legacy_alter_table", "ON")?;
+    }
+
+    let applied = (|| {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        // Nothing enforced foreign keys while the tables were rebuilt
+        if migration.destructive {
+            check_foreign_keys(&tx)?;
+        }
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()
+    })();
+
+    if migration.destructive {
+        conn.pragma_update(None, "legacy_alter_table", "OFF")?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+    }
+    applied
+}
+
+/// Fail if any row references a parent that doesn't exist
+fn check_foreign_keys(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    let mut stmt = tx.prepare("PRAGMA foreign_key_check")?;
+    let mut rows = stmt.query([])?;
+    let Some(row) = rows.next()? else {
+        return Ok(());
+    };
+
+    let table: String = row.get(0)?;
+    let rowid: Option<i64> = row.get(1)?;
+    let parent: String = row.get(2)?;
+    Err(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+        Some(format!(
+            "{table} row {} references a missing {parent} row",
+            rowid.unwrap_or_default()
+        )),
+    ))
+}
+
+/// Copy the database to `<path>.v<version>.bak` before destructive steps run
+fn backup(conn: &Connection, version: i32) -> Result<(), PatinaError> {
+    let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(());
+    };
+
+    // A backup left by an earlier, failed attempt holds the same data
+    let backup_path = format!("{path}.v{version}.bak");
+    match std::fs::remove_file(&backup_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    conn.execute("VACUUM INTO ?1", params![backup_path])?;
+    Ok(())
+}
+
+/// Add columns to existing tables, as (table, column, definition), skipping
+/// those already there
+fn add_columns(tx: &Transaction, columns: &[(&str, &str, &str)]) -> Result<(), rusqlite::Error> {
+    for (table, column, definition) in columns {
+        if !has_column(tx, table, column)? {
+            tx.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, rusqlite::Error> {
+    conn.prepare(&format!(
+        "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+        table
+    ))?
+    .exists(params![column])
+}
+
+fn initial_schema(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS feeds (
+            id INTEGER PRIMARY KEY,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL UNIQUE,
+            site_url TEXT,
+            last_fetched_at INTEGER,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS articles (
+            id INTEGER PRIMARY KEY,
+            feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL,
+            summary TEXT,
+            published_at INTEGER,
+            fetched_at INTEGER NOT NULL,
+            is_read INTEGER DEFAULT 0,
+            read_at INTEGER,
+            UNIQUE(feed_id, url)
+        );
+
+        CREATE TABLE IF NOT EXISTS reading_patterns (
+            id INTEGER PRIMARY KEY,
+            pattern_type TEXT NOT NULL,
+            value TEXT NOT NULL,
+            source TEXT NOT NULL,
+            weight REAL DEFAULT 1.0,
+            created_at INTEGER NOT NULL,
+            UNIQUE(pattern_type, value)
+        );
+
+        CREATE TABLE IF NOT EXISTS article_topics (
+            article_id INTEGER REFERENCES articles(id) ON DELETE CASCADE,
+            topic TEXT NOT NULL,
+            score REAL,
+            PRIMARY KEY(article_id, topic)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_article_topics_topic ON article_topics(topic);
+        "#,
+    )?;
+    tx.execute_batch(ARTICLE_INDEXES)
+}
+
+/// Rebuild the articles table keyed by guid instead of URL, so entries
+/// without a link or sharing one are kept. Existing articles use their URL as
+/// guid, which `insert_article` swaps for the real guid on next sight.
+fn key_articles_by_guid(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    if !has_column(tx, "articles", "guid")? {
+        let columns = tx
+            .prepare("SELECT name FROM pragma_table_info('articles')")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .join(", ");
+
+        tx.execute_batch(&format!(
+            r#"
+            ALTER TABLE articles RENAME TO articles_old;
+
+            CREATE TABLE articles (
+                id INTEGER PRIMARY KEY,
+                feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                summary TEXT,
+                published_at INTEGER,
+                fetched_at INTEGER NOT NULL,
+                is_read INTEGER DEFAULT 0,
+                read_at INTEGER,
+                content_html TEXT,
+                full_text_html TEXT,
+                guid TEXT NOT NULL,
+                UNIQUE(feed_id, guid)
+            );
+
+            INSERT INTO articles ({columns}, guid) SELECT {columns}, url FROM articles_old;
+            DROP TABLE articles_old;
+            "#
+        ))?;
+
+        // The old table took its indexes with it
+        tx.execute_batch(ARTICLE_INDEXES)?;
+    }
+
+    tx.execute_batch("CREATE INDEX IF NOT EXISTS idx_articles_feed_url ON articles(feed_id, url);")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Databases left by each past schema version, before versions were recorded
+    const FIXTURES: &[(i32, &str)] = &[
+        (1, include_str!("../../tests/fixtures/migrations/v1.sql")),
+        (2, include_str!("../../tests/fixtures/migrations/v2.sql")),
+        (3, include_str!("../../tests/fixtures/migrations/v3.sql")),
+        (4, include_str!("../../tests/fixtures/migrations/v4.sql")),
+        (5, include_str!("../../tests/fixtures/migrations/v5.sql")),
+        (6, include_str!("../../tests/fixtures/migrations/v6.sql")),
+        (7, include_str!("../../tests/fixtures/migrations/v7.sql")),
+        (8, include_str!("../../tests/fixtures/migrations/v8.sql")),
+        (9, include_str!("../../tests/fixtures/migrations/v9.sql")),
+        (10, include_str!("../../tests/fixtures/migrations/v10.sql")),
+        (11, include_str!("../../tests/fixtures/migrations/v11.sql")),
+    ];
+
+    /// Columns, indexes and foreign keys of every table, independent of the
+    /// order columns were added in
+    fn schema(conn: &Connection) -> Vec<String> {
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let describe = |sql: &str, arg: &str| -> Vec<String> {
+            let mut stmt = conn.prepare(sql).unwrap();
+            let count = stmt.column_count();
+            stmt.query_map([arg], |row| {
+                (0..count)
+                    .map(|i| {
+                        row.get::<_, rusqlite::types::Value>(i)
+                            .map(|v| format!("{v:?}"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|values| values.join(" "))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+        };
+
+        let mut schema = Vec::new();
+        for table in tables {
+            for column in describe(
+                "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)",
+                &table,
+            ) {
+                schema.push(format!("{table} column {column}"));
+            }
+            for key in describe(
+                "SELECT \"table\", \"from\", \"to\", on_delete FROM pragma_foreign_key_list(?1)",
+                &table,
+            ) {
+                schema.push(format!("{table} foreign key {key}"));
+            }
+
+            // Constraint indexes are named by position, so describe them by their columns
+            let indexes: Vec<(String, bool, String)> = conn
+                .prepare("SELECT name, \"unique\", origin FROM pragma_index_list(?1)")
+                .unwrap()
+                .query_map([&table], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            for (name, unique, origin) in indexes {
+                let columns = describe("SELECT name FROM pragma_index_info(?1)", &name).join(", ");
+                let name = if origin == "c" { name } else { origin };
+                schema.push(format!("{table} index {name} unique={unique} ({columns})"));
+            }
+        }
+
+        schema.sort();
+        schema
+    }
+
+    fn new_database_schema() -> Vec<String> {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        schema(&conn)
+    }
+
+    #[test]
+    fn test_migrate_new_database() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let mut conn = Connection::open(&path).unwrap();
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+
+        // Running again is a no-op, and a new database is never backed up
+        migrate(&mut conn).unwrap();
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_migrate_fixture_databases() {
+        let expected = new_database_schema();
+        assert_eq!(FIXTURES.len() as i32, latest_version());
+
+        for &(version, sql) in FIXTURES {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("test.db");
+            let mut conn = Connection::open(&path).unwrap();
+            conn.execute_batch(sql).unwrap();
+            assert_eq!(schema_version(&conn).unwrap(), 0);
+
+            migrate(&mut conn).unwrap_or_else(|e| panic!("v{version}: {e}"));
+            assert_eq!(schema_version(&conn).unwrap(), latest_version());
+            assert_eq!(schema(&conn), expected, "v{version}");
+
+            // Rows and their state survive
+            let (title, is_read, read_at, guid): (String, bool, i64, String) = conn
+                .query_row(
+                    "SELECT title, is_read, read_at, guid FROM articles WHERE id = 1",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )
+                .unwrap();
+            assert_eq!(title, "First post", "v{version}");
+            assert!(is_read);
+            assert_eq!(read_at, 1_700_000_500);
+            assert_eq!(guid, "https://example.com/1");
+
+            let counts: (i64, i64, i64, i64) = conn
+                .query_row(
+                    r#"
+                    SELECT (SELECT COUNT(*) FROM feeds), (SELECT COUNT(*) FROM articles),
+                           (SELECT COUNT(*) FROM article_topics),
+                           (SELECT COUNT(*) FROM reading_patterns)
+                    "#,
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )
+                .unwrap();
+            assert_eq!(counts, (2, 2, 1, 1), "v{version}");
+
+            let etag: Option<String> = conn
+                .query_row("SELECT etag FROM feeds WHERE id = 1", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(etag.is_some(), version >= 2, "v{version}");
+            let starred: bool = conn
+                .query_row("SELECT starred FROM articles WHERE id = 1", [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(starred, version >= 11, "v{version}");
+
+            // Foreign keys still cascade after the articles table was rebuilt
+            conn.execute("DELETE FROM feeds", []).unwrap();
+            let topics: i64 = conn
+                .query_row("SELECT COUNT(*) FROM article_topics", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(topics, 0, "v{version}");
+
+            // The database was backed up untouched before the rebuild
+            let backup = Connection::open(temp_dir.path().join("test.db.v0.bak")).unwrap();
+            assert_eq!(schema_version(&backup).unwrap(), 0);
+            let articles: i64 = backup
+                .query_row("SELECT COUNT(*) FROM articles", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(articles, 2, "v{version}");
+        }
+    }
+
+    #[test]
+    fn test_broken_foreign_keys_fail_migration() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let mut conn = Connection::open(&path).unwrap();
+        conn.execute_batch(FIXTURES[0].1).unwrap();
+        conn.execute_batch(
+            "INSERT INTO articles VALUES(3,99,'Orphan','https://gone.example/1',NULL,NULL,1700000000,0,NULL);",
+        )
+        .unwrap();
+
+        let error = migrate(&mut conn).unwrap_err().to_string();
+        assert!(error.contains("Migration 4"), "{error}");
+        assert!(error.contains("articles row 3"), "{error}");
+
+        // The rebuild was rolled back, and the backup is there to recover from
+        assert_eq!(schema_version(&conn).unwrap(), 3);
+        assert!(!has_column(&conn, "articles", "guid").unwrap());
+        assert!(temp_dir.path().join("test.db.v0.bak").exists());
+    }
+
+    #[test]
+    fn test_migrate_from_each_version() {
+        let expected = new_database_schema();
+
+        for version in 1..latest_version() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("test.db");
+            let mut conn = Connection::open(&path).unwrap();
+            migrate_to(&mut conn, version).unwrap();
+            assert_eq!(schema_version(&conn).unwrap(), version);
+
+            migrate(&mut conn).unwrap();
+            assert_eq!(schema(&conn), expected, "v{version}");
+
+            // Only databases still to be rebuilt are backed up
+            let backed_up = temp_dir
+                .path()
+                .join(format!("test.db.v{version}.bak"))
+                .exists();
+            assert_eq!(backed_up, version < 4, "v{version}");
+        }
+    }
+
+    #[test]
+    fn test_newer_database_is_rejected() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod db;
+pub mod image_cache;
+pub mod migrations;
 pub mod models;
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use uniffi;
 
 /// A feed subscription
@@ -10,6 +11,60 @@ pub struct Feed {
     pub last_fetched_at: Option<i64>,
     pub created_at: i64,
     pub unread_count: i32,
+    /// Failed fetches since the last success
+    pub consecutive_failures: i32,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<i64>,
+    pub last_http_status: Option<i32>,
+    /// Earliest time a background refresh will fetch this feed again
+    pub next_fetch_at: Option<i64>,
+    /// Set when the feed is gone for good and no longer refreshed
+    pub retired_at: Option<i64>,
+    pub retired_reason: Option<String>,
+    /// Format detected on the last full fetch
+    pub format: Option<FeedFormat>,
+    /// The feed's icon or logo, else the website's favicon
+    pub icon_url: Option<String>,
+    /// Subscribed from an import but not fetched successfully yet
+    pub pending: bool,
+}
+
+/// Syndication format a feed was parsed as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum FeedFormat {
+    /// RSS 0.9x
+    Rss0,
+    /// RSS 1.0 (RDF)
+    Rss1,
+    /// RSS 2.0
+    Rss2,
+    Atom,
+    /// JSON Feed 1.0 or 1.1
+    JsonFeed,
+}
+
+impl FeedFormat {
+    /// Name stored in the `feeds.format` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss0 => "rss0",
+            FeedFormat::Rss1 => "rss1",
+            FeedFormat::Rss2 => "rss2",
+            FeedFormat::Atom => "atom",
+            FeedFormat::JsonFeed => "json",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rss0" => Some(FeedFormat::Rss0),
+            "rss1" => Some(FeedFormat::Rss1),
+            "rss2" => Some(FeedFormat::Rss2),
+            "atom" => Some(FeedFormat::Atom),
+            "json" => Some(FeedFormat::JsonFeed),
+            _ => None,
+        }
+    }
 }
 
 /// An article/entry from a feed
@@ -25,6 +80,97 @@ pub struct Article {
     pub is_read: bool,
     pub read_at: Option<i64>,
     pub feed_title: Option<String>,
+    /// When the publisher last changed the article
+    pub updated_at: Option<i64>,
+    /// Number of edits picked up since the article was first stored
+    pub revision: i32,
+    /// Bylines, in feed order
+    pub authors: Vec<String>,
+    /// Categories and tags, in feed order
+    pub categories: Vec<String>,
+    /// Image to show alongside the article in lists
+    pub thumbnail_url: Option<String>,
+    /// Saved by the reader for later
+    pub starred: bool,
+}
+
+/// Full content of an article for the reader
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ArticleContent {
+    pub article_id: i64,
+    /// Plain-text summary, as shown in article lists
+    pub summary: Option<String>,
+    /// Original markup from the feed, if it carried any
+    pub content_html: Option<String>,
+    /// Main content extracted from the article's web page
+    pub full_text_html: Option<String>,
+}
+
+/// A media file attached to an article, such as a podcast episode's audio
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct Enclosure {
+    pub id: i64,
+    pub article_id: i64,
+    pub url: String,
+    pub mime_type: Option<String>,
+    /// Size in bytes, as declared by the feed
+    pub length: Option<i64>,
+    pub duration_secs: Option<i64>,
+    /// Where the file was saved by `download_enclosure`
+    pub local_path: Option<String>,
+    pub downloaded_at: Option<i64>,
+}
+
+/// iTunes podcast metadata of an episode
+#[derive(Debug, Clone, Default, PartialEq, uniffi::Record)]
+pub struct PodcastEpisode {
+    pub episode: Option<i32>,
+    pub season: Option<i32>,
+    pub duration_secs: Option<i64>,
+    pub explicit: Option<bool>,
+    /// Episode artwork, falling back to the show's
+    pub image_url: Option<String>,
+}
+
+/// Where cached images are kept and how much space they may take
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ImageCacheOptions {
+    pub directory: String,
+    /// Least recently used images are evicted beyond this size
+    pub max_bytes: u64,
+}
+
+/// A folder grouping subscriptions, possibly inside another folder
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct Folder {
+    pub id: i64,
+    pub name: String,
+    /// Enclosing folder, `None` at the top level
+    pub parent_id: Option<i64>,
+    pub created_at: i64,
+    /// Unread articles of the feeds in this folder and its subfolders
+    pub unread_count: i32,
+    /// Feeds in this folder and its subfolders
+    pub feed_count: i32,
+}
+
+/// Per-feed settings
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FeedSettings {
+    /// Extract the full text of new articles from their web pages on refresh
+    pub full_text_enabled: bool,
+    /// Flag articles unread again when the publisher edits them
+    pub mark_updated_unread: bool,
+}
+
+/// Outcome of importing a feed's history
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct BackfillReport {
+    pub feed_id: i64,
+    /// Older pages or archive documents fetched
+    pub pages_fetched: i32,
+    /// Entries stored that the feed no longer carries
+    pub articles_added: i32,
 }
 
 /// A feed discovered from a website
@@ -32,15 +178,140 @@ pub struct Article {
 pub struct DiscoveredFeed {
     pub url: String,
     pub title: Option<String>,
+    /// Format the feed parsed as when it was probed
+    pub format: Option<FeedFormat>,
+    /// Entries the feed currently carries
+    pub entry_count: i32,
+    pub source: DiscoverySource,
+}
+
+/// How a discovered feed was found, in ranking order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, uniffi::Enum)]
+pub enum DiscoverySource {
+    /// Derived from the URL of a well-known platform, such as a YouTube channel
+    Platform,
+    /// Declared by the page with `<link rel="alternate">`
+    LinkRel,
+    /// A feed-like `<a>` link on the page
+    Anchor,
+    /// A common feed path tried on the site
+    CommonPath,
 }
 
 /// Result of importing an OPML file
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct OpmlImportResult {
     pub total_feeds: i32,
+    /// Subscribed and fetched
     pub imported_feeds: i32,
+    /// Subscribed, but not fetched yet; tried again on later refreshes
+    pub pending_feeds: i32,
+    /// Already subscribed, or listed earlier in the file
+    pub skipped_feeds: i32,
+    /// Not subscribed
     pub failed_feeds: i32,
-    pub errors: Vec<String>,
+    /// Outcome of every entry, in file order
+    pub entries: Vec<OpmlEntryOutcome>,
+}
+
+/// What happened to one feed of an OPML file
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct OpmlEntryOutcome {
+    pub url: String,
+    pub title: Option<String>,
+    pub status: OpmlEntryStatus,
+    pub error: Option<String>,
+}
+
+/// Outcome of an OPML entry; a dry run reports what would happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum OpmlEntryStatus {
+    /// Subscribed and fetched
+    Imported,
+    /// Subscribed before the import; filed in the entry's folder
+    AlreadySubscribed,
+    /// Listed earlier in the same file; filed in the entry's folder
+    DuplicateInFile,
+    /// Not subscribed, the URL can't be parsed
+    InvalidUrl,
+    /// Subscribed as pending, the feed couldn't be downloaded
+    FetchFailed,
+    /// Subscribed as pending, the download isn't a feed
+    ParseFailed,
+}
+
+/// Another feed reader's export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ReaderExportFormat {
+    /// Google Reader stream JSON, as exported by Google Takeout, Inoreader and FreshRSS
+    GoogleReader,
+    /// Miniflux entries JSON
+    Miniflux,
+    /// Feedbin subscriptions JSON
+    Feedbin,
+}
+
+/// Result of importing another feed reader's export
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ReaderImportResult {
+    pub format: ReaderExportFormat,
+    /// New subscriptions, fetched on the next refresh
+    pub feeds_added: i32,
+    pub articles_added: i32,
+    /// Articles read in the other reader that weren't read here yet
+    pub read_articles: i32,
+    /// Articles starred in the other reader that weren't starred here yet
+    pub starred_articles: i32,
+}
+
+/// Concurrency limits for refreshing feeds
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RefreshOptions {
+    /// Maximum number of feeds fetched at the same time
+    pub max_concurrent_fetches: u32,
+    /// Maximum number of simultaneous fetches against a single host
+    pub max_fetches_per_host: u32,
+}
+
+impl Default for RefreshOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_fetches: 8,
+            max_fetches_per_host: 2,
+        }
+    }
+}
+
+/// Outcome of refreshing a single feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum RefreshStatus {
+    /// New content was downloaded and stored
+    Updated,
+    /// The server reported no changes since the last fetch
+    NotModified,
+    /// The fetch or parse failed, see `RefreshReport::error`
+    Failed,
+    /// The feed is backing off or retired and was not fetched
+    Skipped,
+    /// The feed is gone or moved onto an existing subscription, and was retired
+    Retired,
+}
+
+/// Per-feed report of a refresh
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RefreshReport {
+    pub feed_id: i64,
+    pub status: RefreshStatus,
+    pub new_articles: i32,
+    /// Ids of the articles this refresh inserted
+    pub new_article_ids: Vec<i64>,
+    /// Articles changed in place since the last fetch
+    pub updated_articles: i32,
+    pub http_status: Option<u16>,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+    /// New subscription URL after a permanent redirect
+    pub moved_to: Option<String>,
 }
 
 /// A reading pattern for serendipity
@@ -55,26 +326,98 @@ pub struct ReadingPattern {
 }
 
 /// Parsed feed data (internal use)
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ParsedFeed {
     pub title: String,
     pub url: String,
     pub site_url: Option<String>,
     pub articles: Vec<ParsedArticle>,
+    /// `ETag` response header, sent back as `If-None-Match` on refresh
+    pub etag: Option<String>,
+    /// `Last-Modified` response header, sent back as `If-Modified-Since` on refresh
+    pub last_modified: Option<String>,
+    pub poll_hints: PollHints,
+    pub format: Option<FeedFormat>,
+    pub icon_url: Option<String>,
+    /// Older entries: RFC 5005 `next` page or `prev-archive` document
+    pub next_page_url: Option<String>,
+}
+
+/// Publisher hints about how often a feed should be polled (internal use)
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollHints {
+    /// RSS `<ttl>`, in minutes
+    pub ttl_minutes: Option<u32>,
+    /// RSS `<skipHours>`, hours (0-23, GMT) when the feed shouldn't be polled
+    pub skip_hours: Vec<u32>,
+    /// RSS `<skipDays>`, days when the feed shouldn't be polled
+    pub skip_days: Vec<chrono::Weekday>,
+    /// `sy:updatePeriod` divided by `sy:updateFrequency`, in seconds
+    pub update_period_secs: Option<i64>,
+}
+
+/// HTTP cache validators stored for a feed (internal use)
+#[derive(Debug, Default, Clone)]
+pub struct FeedValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
 /// Parsed article data (internal use)
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct ParsedArticle {
+    /// Identity within the feed: the entry's id/guid, its link, or a content hash
+    pub guid: String,
     pub title: String,
     pub url: String,
     pub summary: Option<String>,
+    pub content_html: Option<String>,
     pub published_at: Option<i64>,
+    /// When the publisher last modified the entry
+    pub updated_at: Option<i64>,
+    /// Author names, inherited from the feed when the entry has none
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    /// `<enclosure>`, `media:content` and JSON Feed attachments
+    pub enclosures: Vec<ParsedEnclosure>,
+    pub podcast: Option<PodcastEpisode>,
+    pub thumbnail_url: Option<String>,
+}
+
+/// Media attached to a parsed article (internal use)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedEnclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub length: Option<i64>,
+    pub duration_secs: Option<i64>,
 }
 
 /// OPML feed entry (internal use)
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OpmlFeed {
     pub url: String,
     pub title: Option<String>,
+    /// `htmlUrl`, the feed's website
+    pub site_url: Option<String>,
+    /// Names of the enclosing folder outlines, outermost first
+    pub folder: Vec<String>,
+}
+
+/// Subscriptions and articles read from another reader's export (internal use)
+#[derive(Debug)]
+pub struct ReaderExport {
+    pub format: ReaderExportFormat,
+    /// Every feed an item belongs to is listed, by normalized URL
+    pub feeds: Vec<OpmlFeed>,
+    pub items: Vec<ExportedItem>,
+}
+
+/// An article from another reader's export, with its state there (internal use)
+#[derive(Debug, Clone)]
+pub struct ExportedItem {
+    pub feed_url: String,
+    pub article: ParsedArticle,
+    pub is_read: bool,
+    pub starred: bool,
 }
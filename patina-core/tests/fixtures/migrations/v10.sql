@@ -0,0 +1,117 @@
+-- Schema version 10 (pending subscriptions), from before the version was recorded
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE feeds (
+            id INTEGER PRIMARY KEY,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL UNIQUE,
+            site_url TEXT,
+            last_fetched_at INTEGER,
+            created_at INTEGER NOT NULL
+        , etag TEXT, last_modified TEXT, consecutive_failures INTEGER NOT NULL DEFAULT 0, last_error TEXT, last_success_at INTEGER, last_http_status INTEGER, next_fetch_at INTEGER, poll_hints TEXT, retired_at INTEGER, retired_reason TEXT, full_text_enabled INTEGER NOT NULL DEFAULT 0, format TEXT, mark_updated_unread INTEGER NOT NULL DEFAULT 0, icon_url TEXT, pending INTEGER NOT NULL DEFAULT 0);
+INSERT INTO feeds VALUES(1,'Example Blog','https://example.com/feed.xml','https://example.com/',1700000000,1690000000,'"abc123"','Tue, 14 Nov 2023 22:13:20 GMT',0,NULL,1700000000,200,NULL,NULL,NULL,NULL,1,'rss2',0,'https://example.com/favicon.ico',0);
+INSERT INTO feeds VALUES(2,'Example Podcast','https://podcast.example.org/rss',NULL,NULL,1690000000,NULL,NULL,3,'HTTP 503',NULL,503,1700003600,NULL,NULL,NULL,0,NULL,0,NULL,0);
+CREATE TABLE reading_patterns (
+            id INTEGER PRIMARY KEY,
+            pattern_type TEXT NOT NULL,
+            value TEXT NOT NULL,
+            source TEXT NOT NULL,
+            weight REAL DEFAULT 1.0,
+            created_at INTEGER NOT NULL,
+            UNIQUE(pattern_type, value)
+        );
+INSERT INTO reading_patterns VALUES(1,'topic','rust','auto',2.0,1700000500);
+CREATE TABLE article_topics (
+            article_id INTEGER REFERENCES articles(id) ON DELETE CASCADE,
+            topic TEXT NOT NULL,
+            score REAL,
+            PRIMARY KEY(article_id, topic)
+        );
+INSERT INTO article_topics VALUES(1,'rust',0.5);
+CREATE TABLE articles (
+                id INTEGER PRIMARY KEY,
+                feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                summary TEXT,
+                published_at INTEGER,
+                fetched_at INTEGER NOT NULL,
+                is_read INTEGER DEFAULT 0,
+                read_at INTEGER,
+                content_html TEXT,
+                full_text_html TEXT,
+                guid TEXT NOT NULL, content_hash TEXT, updated_at INTEGER, revision INTEGER NOT NULL DEFAULT 0, thumbnail_url TEXT, backfilled INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(feed_id, guid)
+            );
+INSERT INTO articles VALUES(1,1,'First post','https://example.com/1','Hello',1699990000,1700000000,1,1700000500,'<p>Hello</p>',NULL,'https://example.com/1',NULL,NULL,0,'https://example.com/1.png',0);
+INSERT INTO articles VALUES(2,2,'Episode 1','https://podcast.example.org/1',NULL,1699000000,1700000000,0,NULL,NULL,NULL,'tag:podcast.example.org,2023:1',NULL,1699500000,1,NULL,0);
+CREATE TABLE authors (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+                );
+INSERT INTO authors VALUES(1,'Jane Doe');
+CREATE TABLE article_authors (
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY(article_id, author_id)
+                );
+INSERT INTO article_authors VALUES(1,1,0);
+CREATE TABLE categories (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+                );
+INSERT INTO categories VALUES(1,'rust');
+CREATE TABLE article_categories (
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY(article_id, category_id)
+                );
+INSERT INTO article_categories VALUES(1,1,0);
+CREATE TABLE enclosures (
+                    id INTEGER PRIMARY KEY,
+                    article_id INTEGER NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+                    url TEXT NOT NULL,
+                    mime_type TEXT,
+                    length INTEGER,
+                    duration_secs INTEGER,
+                    local_path TEXT,
+                    downloaded_at INTEGER,
+                    UNIQUE(article_id, url)
+                );
+INSERT INTO enclosures VALUES(1,2,'https://podcast.example.org/1.mp3','audio/mpeg',1000,60,NULL,NULL);
+CREATE TABLE podcast_episodes (
+                    article_id INTEGER PRIMARY KEY REFERENCES articles(id) ON DELETE CASCADE,
+                    episode INTEGER,
+                    season INTEGER,
+                    duration_secs INTEGER,
+                    explicit INTEGER,
+                    image_url TEXT
+                );
+INSERT INTO podcast_episodes VALUES(2,1,1,60,NULL,NULL);
+CREATE TABLE folders (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL COLLATE NOCASE,
+                    parent_id INTEGER REFERENCES folders(id) ON DELETE CASCADE,
+                    created_at INTEGER NOT NULL
+                );
+INSERT INTO folders VALUES(1,'Tech',NULL,1690000000);
+CREATE TABLE feed_folders (
+                    feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                    folder_id INTEGER NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+                    PRIMARY KEY(feed_id, folder_id)
+                );
+INSERT INTO feed_folders VALUES(1,1);
+CREATE INDEX idx_article_topics_topic ON article_topics(topic);
+CREATE INDEX idx_articles_feed_id ON articles(feed_id);
+CREATE INDEX idx_articles_is_read ON articles(is_read);
+CREATE INDEX idx_articles_published_at ON articles(published_at);
+CREATE INDEX idx_articles_feed_unread ON articles(feed_id, is_read) WHERE is_read = 0;
+CREATE INDEX idx_articles_date_sort ON articles(published_at DESC);
+CREATE INDEX idx_articles_feed_url ON articles(feed_id, url);
+CREATE INDEX idx_article_authors_author ON article_authors(author_id);
+CREATE INDEX idx_article_categories_category ON article_categories(category_id);
+CREATE INDEX idx_feed_folders_folder ON feed_folders(folder_id);
+CREATE UNIQUE INDEX idx_folders_parent_name ON folders(IFNULL(parent_id, 0), name);
+COMMIT;
@@ -0,0 +1,55 @@
+-- Schema version 4 (key articles by guid instead of url), from before the version was recorded
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE feeds (
+            id INTEGER PRIMARY KEY,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL UNIQUE,
+            site_url TEXT,
+            last_fetched_at INTEGER,
+            created_at INTEGER NOT NULL
+        , etag TEXT, last_modified TEXT, consecutive_failures INTEGER NOT NULL DEFAULT 0, last_error TEXT, last_success_at INTEGER, last_http_status INTEGER, next_fetch_at INTEGER, poll_hints TEXT, retired_at INTEGER, retired_reason TEXT, full_text_enabled INTEGER NOT NULL DEFAULT 0, format TEXT);
+INSERT INTO feeds VALUES(1,'Example Blog','https://example.com/feed.xml','https://example.com/',1700000000,1690000000,'"abc123"','Tue, 14 Nov 2023 22:13:20 GMT',0,NULL,1700000000,200,NULL,NULL,NULL,NULL,1,'rss2');
+INSERT INTO feeds VALUES(2,'Example Podcast','https://podcast.example.org/rss',NULL,NULL,1690000000,NULL,NULL,3,'HTTP 503',NULL,503,1700003600,NULL,NULL,NULL,0,NULL);
+CREATE TABLE reading_patterns (
+            id INTEGER PRIMARY KEY,
+            pattern_type TEXT NOT NULL,
+            value TEXT NOT NULL,
+            source TEXT NOT NULL,
+            weight REAL DEFAULT 1.0,
+            created_at INTEGER NOT NULL,
+            UNIQUE(pattern_type, value)
+        );
+INSERT INTO reading_patterns VALUES(1,'topic','rust','auto',2.0,1700000500);
+CREATE TABLE article_topics (
+            article_id INTEGER REFERENCES articles(id) ON DELETE CASCADE,
+            topic TEXT NOT NULL,
+            score REAL,
+            PRIMARY KEY(article_id, topic)
+        );
+INSERT INTO article_topics VALUES(1,'rust',0.5);
+CREATE TABLE articles (
+                id INTEGER PRIMARY KEY,
+                feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                summary TEXT,
+                published_at INTEGER,
+                fetched_at INTEGER NOT NULL,
+                is_read INTEGER DEFAULT 0,
+                read_at INTEGER,
+                content_html TEXT,
+                full_text_html TEXT,
+                guid TEXT NOT NULL,
+                UNIQUE(feed_id, guid)
+            );
+INSERT INTO articles VALUES(1,1,'First post','https://example.com/1','Hello',1699990000,1700000000,1,1700000500,'<p>Hello</p>',NULL,'https://example.com/1');
+INSERT INTO articles VALUES(2,2,'Episode 1','https://podcast.example.org/1',NULL,1699000000,1700000000,0,NULL,NULL,NULL,'tag:podcast.example.org,2023:1');
+CREATE INDEX idx_article_topics_topic ON article_topics(topic);
+CREATE INDEX idx_articles_feed_id ON articles(feed_id);
+CREATE INDEX idx_articles_is_read ON articles(is_read);
+CREATE INDEX idx_articles_published_at ON articles(published_at);
+CREATE INDEX idx_articles_feed_unread ON articles(feed_id, is_read) WHERE is_read = 0;
+CREATE INDEX idx_articles_date_sort ON articles(published_at DESC);
+CREATE INDEX idx_articles_feed_url ON articles(feed_id, url);
+COMMIT;
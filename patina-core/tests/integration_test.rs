@@ -1,4 +1,7 @@
+use patina_core::feed::opml::ImportProgress;
+use patina_core::storage::models::OpmlEntryStatus;
 use patina_core::{create_patina_core, hello_from_rust};
+use std::sync::{Arc, Mutex};
 
 #[test]
 fn test_hello_from_rust() {
@@ -98,7 +101,7 @@ fn test_opml_import() {
     </opml>"#;
 
     println!("Importing OPML...");
-    let result = core.import_opml(opml_content.to_string());
+    let result = core.import_opml(opml_content.to_string(), None);
 
     match result {
         Ok(r) => {
@@ -107,6 +110,15 @@ fn test_opml_import() {
                 r.total_feeds, r.imported_feeds, r.failed_feeds
             );
             assert_eq!(r.total_feeds, 1);
+
+            // The outline's folder comes along with the feed
+            if r.imported_feeds == 1 {
+                let folders = core.get_all_folders().unwrap();
+                assert_eq!(folders.len(), 1);
+                assert_eq!(folders[0].name, "Tech");
+                assert_eq!(folders[0].feed_count, 1);
+                println!("✓ Feed filed in folder {}", folders[0].name);
+            }
         }
         Err(e) => {
             println!("⚠ OPML import failed (network?): {}", e);
@@ -114,6 +126,151 @@ fn test_opml_import() {
     }
 }
 
+/// Imported, pending, skipped, failed and total counts, and the current URL
+type ProgressReport = (u32, u32, u32, u32, u32, String);
+
+/// Records every progress report of an import
+#[derive(Default)]
+struct RecordedProgress(Mutex<Vec<ProgressReport>>);
+
+impl ImportProgress for RecordedProgress {
+    fn on_progress(
+        &self,
+        imported: u32,
+        pending: u32,
+        skipped: u32,
+        failed: u32,
+        total: u32,
+        current_url: String,
+    ) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((imported, pending, skipped, failed, total, current_url));
+    }
+}
+
+#[test]
+fn test_opml_import_unreachable_feeds() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+
+    let core = create_patina_core(db_path.to_string_lossy().to_string()).unwrap();
+
+    // Nothing listens on the discard port, so the feed can't be fetched
+    let opml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <opml version="2.0">
+        <head><title>Test Feeds</title></head>
+        <body>
+            <outline text="Offline">
+                <outline type="rss" text="Unreachable Blog"
+                         xmlUrl="http://127.0.0.1:9/feed.xml"
+                         htmlUrl="http://127.0.0.1:9/"/>
+            </outline>
+            <outline type="rss" text="Broken" xmlUrl="not a url"/>
+        </body>
+    </opml>"#;
+
+    let progress = Arc::new(RecordedProgress::default());
+    let result = core
+        .import_opml(opml_content.to_string(), Some(progress.clone()))
+        .unwrap();
+    assert_eq!(result.total_feeds, 2);
+    assert_eq!(result.imported_feeds, 0);
+    assert_eq!(result.pending_feeds, 1);
+    assert_eq!(result.failed_feeds, 1);
+
+    let statuses: Vec<_> = result.entries.iter().map(|e| e.status).collect();
+    assert_eq!(
+        statuses,
+        vec![OpmlEntryStatus::FetchFailed, OpmlEntryStatus::InvalidUrl]
+    );
+    assert_eq!(result.entries[0].title.as_deref(), Some("Unreachable Blog"));
+    assert!(result.entries[0].error.is_some());
+
+    // The feed stays subscribed under its OPML title until it can be fetched
+    let feeds = core.get_all_feeds().unwrap();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].title, "Unreachable Blog");
+    assert_eq!(feeds[0].site_url.as_deref(), Some("http://127.0.0.1:9/"));
+    assert!(feeds[0].pending);
+    assert_eq!(
+        core.get_feeds_in_folder(core.get_all_folders().unwrap()[0].id)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let reports = progress.0.lock().unwrap();
+    assert_eq!(
+        *reports,
+        vec![
+            (0, 0, 0, 1, 2, "not a url".to_string()),
+            (0, 1, 0, 1, 2, "http://127.0.0.1:9/feed.xml".to_string()),
+        ]
+    );
+    println!("✓ Unreachable feed kept as pending");
+}
+
+#[test]
+fn test_opml_import_preview() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+
+    let core = create_patina_core(db_path.to_string_lossy().to_string()).unwrap();
+
+    let subscribed = r#"<opml version="2.0"><body>
+        <outline type="rss" text="Old" xmlUrl="http://127.0.0.1:9/old.xml"/>
+    </body></opml>"#;
+    core.import_opml(subscribed.to_string(), None).unwrap();
+
+    let opml_content = r#"<opml version="2.0"><body>
+        <outline text="News">
+            <outline type="rss" text="Old" xmlUrl="http://127.0.0.1:9/old.xml"/>
+            <outline type="rss" text="New" xmlUrl="http://127.0.0.1:9/new.xml"/>
+        </outline>
+        <outline type="rss" text="New again" xmlUrl="http://127.0.0.1:9/new.xml"/>
+        <outline type="rss" text="Broken" xmlUrl="::"/>
+    </body></opml>"#;
+
+    let progress = Arc::new(RecordedProgress::default());
+    let preview = core
+        .preview_opml_import(opml_content.to_string(), Some(progress.clone()))
+        .unwrap();
+    let outcomes: Vec<_> = preview
+        .entries
+        .iter()
+        .map(|e| (e.title.as_deref().unwrap(), e.status))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("Old", OpmlEntryStatus::AlreadySubscribed),
+            ("New", OpmlEntryStatus::FetchFailed),
+            ("New again", OpmlEntryStatus::DuplicateInFile),
+            ("Broken", OpmlEntryStatus::InvalidUrl),
+        ]
+    );
+    assert_eq!(
+        (
+            preview.pending_feeds,
+            preview.skipped_feeds,
+            preview.failed_feeds
+        ),
+        (1, 2, 1)
+    );
+
+    // The last progress report agrees with the result
+    let (imported, pending, skipped, failed, total, _) =
+        progress.0.lock().unwrap().last().cloned().unwrap();
+    assert_eq!((imported, pending, skipped, failed, total), (0, 1, 2, 1, 4));
+
+    // Nothing was written
+    assert_eq!(core.get_all_feeds().unwrap().len(), 1);
+    assert!(core.get_all_folders().unwrap().is_empty());
+    println!("✓ OPML preview left the library untouched");
+}
+
 #[test]
 fn test_reading_patterns() {
     let temp_dir = tempfile::tempdir().unwrap();